@@ -9,6 +9,12 @@ const PYTHONSYS_ENV_VAR: &'static str = "DEP_PYTHON27_PYTHON_FLAGS";
 #[cfg(feature="python3-sys")]
 const PYTHONSYS_ENV_VAR: &'static str = "DEP_PYTHON3_PYTHON_FLAGS";
 
+#[cfg(feature="python27-sys")]
+const PYTHONSYS_CFGS_ENV_VAR: &'static str = "DEP_PYTHON27_PYTHON_CFGS";
+
+#[cfg(feature="python3-sys")]
+const PYTHONSYS_CFGS_ENV_VAR: &'static str = "DEP_PYTHON3_PYTHON_CFGS";
+
 fn main() {
     // python{27,3.x}-sys/build.rs passes python interpreter compile flags via 
     // environment variable (using the 'links' mechanism in the cargo.toml).
@@ -37,4 +43,12 @@ fn main() {
             }
         }
     }
+
+    // python{27,3.x}-sys/build.rs also passes the version cfgs (e.g. `Py_3_6`)
+    // it was compiled with, so that the same #cfg blocks can be used here.
+    if let Ok(cfgs) = env::var(PYTHONSYS_CFGS_ENV_VAR) {
+        for cfg in cfgs.split(",").filter(|cfg| cfg.len() > 0) {
+            println!("cargo:rustc-cfg={}", cfg);
+        }
+    }
 }
@@ -0,0 +1,454 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! `#[derive(ToPyObject, FromPyObject)]` for the `cpython` crate.
+//!
+//! # Representations
+//! Structs with named fields are represented as a `dict` by default.
+//! The representation can be changed with an attribute on the struct:
+//!
+//! * `#[cpython(dict)]`: `{"field": value, ...}`. Any mapping can be extracted.
+//! * `#[cpython(attributes)]`: an object with an attribute per field.
+//!   Extraction works with any object; conversion creates a `types.SimpleNamespace`
+//!   (an `argparse.Namespace` on Python 2). The type is imported on the first conversion;
+//!   `to_py_object` panics if that import fails.
+//! * `#[cpython(tuple)]`: `(value, ...)` in the order of the fields.
+//!   Lists of the right length are accepted as well.
+//!
+//! Tuple structs are represented as tuples, except for newtype structs
+//! (tuple structs with a single field), which are represented like the wrapped value.
+//! Unit structs are represented as `None`.
+//!
+//! Enums are converted like their variants (which may also use the attributes above),
+//! with unit variants represented as a string of their name.
+//! An enum is extracted by trying each variant in turn; the first one that can be
+//! extracted successfully is used.
+//!
+//! # Field attributes
+//! * `#[cpython(rename = "name")]`: use a different key (or attribute name) in Python.
+//!   On enum variants, this renames the string representing a unit variant.
+//! * `#[cpython(default)]`: use `Default::default()` when the key or attribute is missing.
+//! * `#[cpython(default = "path::to::function")]`: call the function when the key or attribute is missing.
+//!
+//! # Example
+//! ```ignore
+//! #[macro_use] extern crate cpython_derive;
+//!
+//! #[derive(ToPyObject, FromPyObject)]
+//! struct Point {
+//!     x: f64,
+//!     y: f64,
+//!     #[cpython(default)]
+//!     label: Option<String>,
+//! }
+//! ```
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+#[macro_use]
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::ToTokens;
+use syn::{Attribute, Data, DeriveInput, Fields, Ident, Index, Lit, Member, Meta, NestedMeta, Path, Type};
+use syn::spanned::Spanned;
+
+#[proc_macro_derive(ToPyObject, attributes(cpython))]
+pub fn derive_to_py_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match impl_to_py_object(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into()
+    }
+}
+
+#[proc_macro_derive(FromPyObject, attributes(cpython))]
+pub fn derive_from_py_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match impl_from_py_object(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into()
+    }
+}
+
+/// How a struct or enum variant is represented in Python.
+#[derive(Clone, Copy, PartialEq)]
+enum Style {
+    Dict,
+    Attributes,
+    Tuple,
+    Transparent,
+    Unit
+}
+
+enum DefaultValue {
+    Trait,
+    Function(Path)
+}
+
+struct Field {
+    member: Member,
+    /// Name of the local variable the field is bound to in patterns
+    binding: Ident,
+    /// Name of the field in Python
+    key: String,
+    ty: Type,
+    default: Option<DefaultValue>
+}
+
+/// A struct, or a variant of an enum.
+struct Shape {
+    /// Path used to construct or match the value (`Name` or `Name::Variant`)
+    path: TokenStream2,
+    /// Name used in error messages
+    description: String,
+    /// Python name of the variant (for unit variants)
+    key: String,
+    style: Style,
+    fields: Vec<Field>
+}
+
+/// Gets the items of all `#[cpython(...)]` attributes.
+fn cpython_attrs(attrs: &[Attribute]) -> syn::Result<Vec<Meta>> {
+    let mut result = Vec::new();
+    for attr in attrs {
+        if !attr.path.is_ident("cpython") {
+            continue;
+        }
+        match attr.parse_meta()? {
+            Meta::List(list) => {
+                for nested in list.nested {
+                    match nested {
+                        NestedMeta::Meta(meta) => result.push(meta),
+                        NestedMeta::Lit(lit) => return Err(syn::Error::new_spanned(lit, "unexpected literal"))
+                    }
+                }
+            }
+            meta => return Err(syn::Error::new_spanned(meta, "expected #[cpython(...)]"))
+        }
+    }
+    Ok(result)
+}
+
+fn string_value(meta: &Meta) -> syn::Result<String> {
+    if let Meta::NameValue(ref nv) = *meta {
+        if let Lit::Str(ref s) = nv.lit {
+            return Ok(s.value());
+        }
+    }
+    Err(syn::Error::new_spanned(meta, "expected a string value"))
+}
+
+/// Parses the representation (`dict`, `attributes` or `tuple`) and `rename`
+/// attributes of a struct or enum variant.
+fn parse_shape_attrs(attrs: &[Attribute], allow_rename: bool) -> syn::Result<(Option<Style>, Option<String>)> {
+    let mut style = None;
+    let mut rename = None;
+    for meta in cpython_attrs(attrs)? {
+        let new_style = if meta.path().is_ident("dict") {
+            Style::Dict
+        } else if meta.path().is_ident("attributes") {
+            Style::Attributes
+        } else if meta.path().is_ident("tuple") {
+            Style::Tuple
+        } else if allow_rename && meta.path().is_ident("rename") {
+            rename = Some(string_value(&meta)?);
+            continue;
+        } else {
+            return Err(syn::Error::new_spanned(meta, "unknown cpython attribute"));
+        };
+        if let Meta::Path(_) = meta {
+        } else {
+            return Err(syn::Error::new_spanned(meta, "unexpected value"));
+        }
+        if style.is_some() {
+            return Err(syn::Error::new_spanned(meta, "conflicting representation attributes"));
+        }
+        style = Some(new_style);
+    }
+    Ok((style, rename))
+}
+
+fn parse_fields(fields: &Fields) -> syn::Result<Vec<Field>> {
+    let mut result = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let member = match field.ident {
+            Some(ref ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index { index: index as u32, span: field.span() })
+        };
+        let mut key = match field.ident {
+            Some(ref ident) => ident.to_string(),
+            None => index.to_string()
+        };
+        let mut default = None;
+        for meta in cpython_attrs(&field.attrs)? {
+            if meta.path().is_ident("rename") {
+                key = string_value(&meta)?;
+            } else if meta.path().is_ident("default") {
+                default = Some(match meta {
+                    Meta::Path(_) => DefaultValue::Trait,
+                    _ => DefaultValue::Function(syn::parse_str(&string_value(&meta)?)?)
+                });
+            } else {
+                return Err(syn::Error::new_spanned(meta, "unknown cpython attribute"));
+            }
+        }
+        result.push(Field {
+            member: member,
+            binding: Ident::new(&format!("__field{}", index), Span::call_site()),
+            key: key,
+            ty: field.ty.clone(),
+            default: default
+        });
+    }
+    Ok(result)
+}
+
+fn parse_shape(path: TokenStream2, name: String, attrs: &[Attribute], fields: &Fields, is_variant: bool)
+    -> syn::Result<Shape>
+{
+    let (style, rename) = parse_shape_attrs(attrs, is_variant)?;
+    let style = match (fields, style) {
+        (&Fields::Named(_), style) => style.unwrap_or(Style::Dict),
+        (&Fields::Unnamed(ref unnamed), None) if unnamed.unnamed.len() == 1 => Style::Transparent,
+        (&Fields::Unnamed(_), None) | (&Fields::Unnamed(_), Some(Style::Tuple)) => Style::Tuple,
+        (&Fields::Unit, None) => Style::Unit,
+        (_, Some(_)) => return Err(syn::Error::new_spanned(fields,
+            "the dict and attributes representations require named fields"))
+    };
+    Ok(Shape {
+        path: path,
+        key: rename.unwrap_or_else(|| name.clone()),
+        description: name,
+        style: style,
+        fields: parse_fields(fields)?
+    })
+}
+
+/// Gets the shapes of the struct, or of each variant of the enum.
+fn parse_shapes(input: &DeriveInput) -> syn::Result<Vec<Shape>> {
+    let name = &input.ident;
+    match input.data {
+        Data::Struct(ref data) => {
+            Ok(vec![parse_shape(quote!(#name), name.to_string(), &input.attrs, &data.fields, false)?])
+        }
+        Data::Enum(ref data) => {
+            if let Some(meta) = cpython_attrs(&input.attrs)?.into_iter().next() {
+                return Err(syn::Error::new_spanned(meta, "representation attributes must be placed on the variants"));
+            }
+            if data.variants.is_empty() {
+                return Err(syn::Error::new_spanned(input, "cannot derive conversions for an enum without variants"));
+            }
+            data.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                parse_shape(quote!(#name::#ident), format!("{}::{}", name, ident),
+                            &variant.attrs, &variant.fields, true)
+            }).collect()
+        }
+        Data::Union(_) => Err(syn::Error::new_spanned(input, "cannot derive conversions for a union"))
+    }
+}
+
+fn to_object(value: TokenStream2) -> TokenStream2 {
+    quote!(::cpython::PythonObject::into_object(::cpython::ToPyObject::into_py_object(#value, py)))
+}
+
+/// Generates a match arm converting the shape into a `PyObject`.
+fn to_py_object_arm(shape: &Shape, is_enum: bool) -> TokenStream2 {
+    let path = &shape.path;
+    let members = shape.fields.iter().map(|f| &f.member);
+    let bindings = shape.fields.iter().map(|f| &f.binding);
+    let pattern = quote!(#path { #(#members: #bindings),* });
+    let body = match shape.style {
+        Style::Dict | Style::Attributes => {
+            let items = shape.fields.iter().map(|f| {
+                let key = &f.key;
+                let value = to_object(f.binding.clone().into_token_stream());
+                quote!(::cpython::_detail::derive::set_item(py, &dict, #key, #value);)
+            });
+            let result = if shape.style == Style::Dict {
+                quote!(::cpython::PythonObject::into_object(dict))
+            } else {
+                quote!(::cpython::_detail::derive::namespace(py, dict))
+            };
+            quote!({
+                let dict = ::cpython::PyDict::new(py);
+                #(#items)*
+                #result
+            })
+        }
+        Style::Tuple => {
+            let items = shape.fields.iter().map(|f| to_object(f.binding.clone().into_token_stream()));
+            quote!(::cpython::PythonObject::into_object(::cpython::PyTuple::new(py, &[#(#items),*])))
+        }
+        Style::Transparent => to_object(shape.fields[0].binding.clone().into_token_stream()),
+        Style::Unit if is_enum => {
+            let key = &shape.key;
+            quote!(::cpython::PythonObject::into_object(::cpython::PyString::new(py, #key)))
+        }
+        Style::Unit => quote!(py.None())
+    };
+    quote!(#pattern => #body,)
+}
+
+fn impl_to_py_object(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let shapes = parse_shapes(input)?;
+    let is_enum = match input.data { Data::Enum(_) => true, _ => false };
+    let mut generics = input.generics.clone();
+    if generics.type_params().next().is_some() {
+        let where_clause = generics.make_where_clause();
+        for field in shapes.iter().flat_map(|s| s.fields.iter()) {
+            let ty = &field.ty;
+            where_clause.predicates.push(parse_quote!(#ty: ::cpython::ToPyObject));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let name = &input.ident;
+    let arms: Vec<TokenStream2> = shapes.iter().map(|shape| to_py_object_arm(shape, is_enum)).collect();
+    let doc = if shapes.iter().any(|shape| shape.style == Style::Attributes) {
+        quote!(#[doc = "Panics if the namespace type used for `#[cpython(attributes)]` cannot be imported."])
+    } else {
+        quote!()
+    };
+    Ok(quote! {
+        #doc
+        impl #impl_generics ::cpython::ToPyObject for #name #ty_generics #where_clause {
+            type ObjectType = ::cpython::PyObject;
+
+            fn to_py_object(&self, py: ::cpython::Python) -> ::cpython::PyObject {
+                match self {
+                    #(#arms)*
+                }
+            }
+
+            fn into_py_object(self, py: ::cpython::Python) -> ::cpython::PyObject {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+/// Generates an expression of type `PyResult<Self>` that extracts the shape from `obj`.
+fn extract_shape(shape: &Shape, is_enum: bool) -> TokenStream2 {
+    let path = &shape.path;
+    let description = &shape.description;
+    match shape.style {
+        Style::Dict | Style::Attributes => {
+            let (getter, missing) = if shape.style == Style::Dict {
+                (quote!(get_item), quote!(missing_item))
+            } else {
+                (quote!(get_attr), quote!(missing_attr))
+            };
+            let inits = shape.fields.iter().map(|f| {
+                let member = &f.member;
+                let key = &f.key;
+                let on_missing = match f.default {
+                    None => quote!(return Err(::cpython::_detail::derive::#missing(py, #description, #key))),
+                    Some(DefaultValue::Trait) => quote!(::std::default::Default::default()),
+                    Some(DefaultValue::Function(ref function)) => quote!(#function())
+                };
+                quote! {
+                    #member: match ::cpython::_detail::derive::#getter(py, obj, #key)? {
+                        Some(value) => ::cpython::_detail::derive::extract_field(py, &value, #description, #key)?,
+                        None => #on_missing
+                    }
+                }
+            });
+            quote!({ Ok(#path { #(#inits),* }) })
+        }
+        Style::Tuple => {
+            let len = shape.fields.len();
+            let inits = shape.fields.iter().enumerate().map(|(index, f)| {
+                let member = &f.member;
+                let key = &f.key;
+                quote!(#member: ::cpython::_detail::derive::extract_field(py, &items[#index], #description, #key)?)
+            });
+            quote!({
+                let items = ::cpython::_detail::derive::tuple_items(py, obj, #description, #len)?;
+                Ok(#path { #(#inits),* })
+            })
+        }
+        Style::Transparent => {
+            quote!(Ok(#path { 0: ::cpython::FromPyObject::extract(py, obj)? }))
+        }
+        Style::Unit if is_enum => {
+            let key = &shape.key;
+            quote!({
+                ::cpython::_detail::derive::unit_variant(py, obj, #key)?;
+                Ok(#path {})
+            })
+        }
+        Style::Unit => {
+            quote!({
+                ::cpython::_detail::derive::unit_struct(py, obj, #description)?;
+                Ok(#path {})
+            })
+        }
+    }
+}
+
+fn impl_from_py_object(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let shapes = parse_shapes(input)?;
+    let name = &input.ident;
+    let body = match input.data {
+        Data::Enum(_) => {
+            let attempts = shapes.iter().map(|shape| {
+                let description = &shape.description;
+                let extract = extract_shape(shape, true);
+                quote! {
+                    match (|| -> ::cpython::PyResult<Self> { #extract })() {
+                        Ok(value) => return Ok(value),
+                        Err(e) => errors.push((#description, e))
+                    }
+                }
+            });
+            let name = name.to_string();
+            quote!({
+                let mut errors = Vec::new();
+                #(#attempts)*
+                Err(::cpython::_detail::derive::no_variant_matched(py, #name, errors))
+            })
+        }
+        _ => extract_shape(&shapes[0], false)
+    };
+
+    let mut generics = input.generics.clone();
+    if generics.type_params().next().is_some() {
+        let where_clause = generics.make_where_clause();
+        for field in shapes.iter().flat_map(|s| s.fields.iter()) {
+            let ty = &field.ty;
+            where_clause.predicates.push(parse_quote!(for<'__a> #ty: ::cpython::FromPyObject<'__a>));
+        }
+    }
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let mut impl_generics = generics.clone();
+    impl_generics.params.insert(0, parse_quote!('source));
+    let (impl_generics, _, _) = impl_generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::cpython::FromPyObject<'source> for #name #ty_generics #where_clause {
+            fn extract(py: ::cpython::Python, obj: &'source ::cpython::PyObject) -> ::cpython::PyResult<Self> {
+                #body
+            }
+        }
+    })
+}
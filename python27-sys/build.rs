@@ -336,16 +336,23 @@ fn configure_from_path(expected_version: &PythonVersion) -> Result<String, Strin
     }
 // ~~~~~~~~~~ generated file, modify `python3-sys/build.rs` ~~~~~~~~~~
 // ~~~~~~~~~~ generated file, modify `python3-sys/build.rs` ~~~~~~~~~~
+    let mut cfgs = Vec::new();
     if let PythonVersion { major: 3, minor: some_minor} = interpreter_version {
         if env::var_os("CARGO_FEATURE_PEP_384").is_some() {
-            println!("cargo:rustc-cfg=Py_LIMITED_API");
+            cfgs.push("Py_LIMITED_API".to_owned());
         }
         if let Some(minor) = some_minor {
             for i in 4..(minor+1) {
-                println!("cargo:rustc-cfg=Py_3_{}", i);
+                cfgs.push(format!("Py_3_{}", i));
             }
         }
     }
+    for cfg in &cfgs {
+        println!("cargo:rustc-cfg={}", cfg);
+    }
+    // Export the cfgs so that dependents can use the same cfg syntax,
+    // see rust-cpython/build.rs
+    println!("cargo:python_cfgs={}", cfgs.join(","));
 // ~~~~~~~~~~ generated file, modify `python3-sys/build.rs` ~~~~~~~~~~
     return Ok(interpreter_path);
 }
@@ -0,0 +1,268 @@
+// Bindings for the `datetime` C-API (Include/datetime.h).
+// The C-API is provided by the `datetime` module
+// as a capsule, which must be imported with `PyDateTime_IMPORT()` before any of the
+// functions in this module are used.
+
+use libc::{c_char, c_int, c_long, c_uchar};
+use object::*;
+use pycapsule::PyCapsule_Import;
+
+pub const _PyDateTime_DATE_DATASIZE: usize = 4;
+pub const _PyDateTime_TIME_DATASIZE: usize = 6;
+pub const _PyDateTime_DATETIME_DATASIZE: usize = 10;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDateTime_Delta {
+    pub ob_base: PyObject,
+    pub hashcode: c_long,
+    pub days: c_int,
+    pub seconds: c_int,
+    pub microseconds: c_int,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDateTime_TZInfo {
+    pub ob_base: PyObject,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDateTime_Date {
+    pub ob_base: PyObject,
+    pub hashcode: c_long,
+    pub hastzinfo: c_char,
+    pub data: [c_uchar; _PyDateTime_DATE_DATASIZE],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDateTime_Time {
+    pub ob_base: PyObject,
+    pub hashcode: c_long,
+    pub hastzinfo: c_char,
+    pub data: [c_uchar; _PyDateTime_TIME_DATASIZE],
+    /// Only valid if `hastzinfo` is true.
+    pub tzinfo: *mut PyObject,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDateTime_DateTime {
+    pub ob_base: PyObject,
+    pub hashcode: c_long,
+    pub hastzinfo: c_char,
+    pub data: [c_uchar; _PyDateTime_DATETIME_DATASIZE],
+    /// Only valid if `hastzinfo` is true.
+    pub tzinfo: *mut PyObject,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDateTime_CAPI {
+    pub DateType: *mut PyTypeObject,
+    pub DateTimeType: *mut PyTypeObject,
+    pub TimeType: *mut PyTypeObject,
+    pub DeltaType: *mut PyTypeObject,
+    pub TZInfoType: *mut PyTypeObject,
+
+    pub Date_FromDate: unsafe extern "C" fn(year: c_int, month: c_int, day: c_int,
+                                            cls: *mut PyTypeObject) -> *mut PyObject,
+    pub DateTime_FromDateAndTime: unsafe extern "C" fn(year: c_int, month: c_int, day: c_int,
+                                                       hour: c_int, minute: c_int, second: c_int,
+                                                       microsecond: c_int, tzinfo: *mut PyObject,
+                                                       cls: *mut PyTypeObject) -> *mut PyObject,
+    pub Time_FromTime: unsafe extern "C" fn(hour: c_int, minute: c_int, second: c_int,
+                                            microsecond: c_int, tzinfo: *mut PyObject,
+                                            cls: *mut PyTypeObject) -> *mut PyObject,
+    pub Delta_FromDelta: unsafe extern "C" fn(days: c_int, seconds: c_int, microseconds: c_int,
+                                              normalize: c_int,
+                                              cls: *mut PyTypeObject) -> *mut PyObject,
+
+    pub DateTime_FromTimestamp: unsafe extern "C" fn(cls: *mut PyObject, args: *mut PyObject,
+                                                     kwargs: *mut PyObject) -> *mut PyObject,
+    pub Date_FromTimestamp: unsafe extern "C" fn(cls: *mut PyObject,
+                                                 args: *mut PyObject) -> *mut PyObject,
+}
+
+pub const PyDateTime_CAPSULE_NAME: &'static [u8] = b"datetime.datetime_CAPI\0";
+
+/// The C-API of the `datetime` module; null until `PyDateTime_IMPORT()` is called.
+pub static mut PyDateTimeAPI: *mut PyDateTime_CAPI = 0 as *mut PyDateTime_CAPI;
+
+/// Imports the `datetime` C-API into `PyDateTimeAPI`.
+/// On failure, `PyDateTimeAPI` remains null and a Python exception is set.
+#[inline]
+pub unsafe fn PyDateTime_IMPORT() {
+    PyDateTimeAPI = PyCapsule_Import(PyDateTime_CAPSULE_NAME.as_ptr() as *const c_char, 0)
+        as *mut PyDateTime_CAPI;
+}
+
+// Type-check functions
+
+#[inline(always)]
+pub unsafe fn PyDate_Check(op: *mut PyObject) -> c_int {
+    PyObject_TypeCheck(op, (*PyDateTimeAPI).DateType)
+}
+
+#[inline(always)]
+pub unsafe fn PyDate_CheckExact(op: *mut PyObject) -> c_int {
+    (Py_TYPE(op) == (*PyDateTimeAPI).DateType) as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_Check(op: *mut PyObject) -> c_int {
+    PyObject_TypeCheck(op, (*PyDateTimeAPI).DateTimeType)
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_CheckExact(op: *mut PyObject) -> c_int {
+    (Py_TYPE(op) == (*PyDateTimeAPI).DateTimeType) as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyTime_Check(op: *mut PyObject) -> c_int {
+    PyObject_TypeCheck(op, (*PyDateTimeAPI).TimeType)
+}
+
+#[inline(always)]
+pub unsafe fn PyTime_CheckExact(op: *mut PyObject) -> c_int {
+    (Py_TYPE(op) == (*PyDateTimeAPI).TimeType) as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDelta_Check(op: *mut PyObject) -> c_int {
+    PyObject_TypeCheck(op, (*PyDateTimeAPI).DeltaType)
+}
+
+#[inline(always)]
+pub unsafe fn PyDelta_CheckExact(op: *mut PyObject) -> c_int {
+    (Py_TYPE(op) == (*PyDateTimeAPI).DeltaType) as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyTZInfo_Check(op: *mut PyObject) -> c_int {
+    PyObject_TypeCheck(op, (*PyDateTimeAPI).TZInfoType)
+}
+
+#[inline(always)]
+pub unsafe fn PyTZInfo_CheckExact(op: *mut PyObject) -> c_int {
+    (Py_TYPE(op) == (*PyDateTimeAPI).TZInfoType) as c_int
+}
+
+// Accessors; these do not check the type of the argument.
+// The `PyDateTime_GET_*` accessors work for both `date` and `datetime` objects.
+
+#[inline(always)]
+pub unsafe fn PyDateTime_GET_YEAR(o: *mut PyObject) -> c_int {
+    let data = &(*(o as *mut PyDateTime_Date)).data;
+    ((data[0] as c_int) << 8) | data[1] as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_GET_MONTH(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_Date)).data[2] as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_GET_DAY(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_Date)).data[3] as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_DATE_GET_HOUR(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_DateTime)).data[4] as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_DATE_GET_MINUTE(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_DateTime)).data[5] as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_DATE_GET_SECOND(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_DateTime)).data[6] as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_DATE_GET_MICROSECOND(o: *mut PyObject) -> c_int {
+    let data = &(*(o as *mut PyDateTime_DateTime)).data;
+    ((data[7] as c_int) << 16) | ((data[8] as c_int) << 8) | data[9] as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_TIME_GET_HOUR(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_Time)).data[0] as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_TIME_GET_MINUTE(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_Time)).data[1] as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_TIME_GET_SECOND(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_Time)).data[2] as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_TIME_GET_MICROSECOND(o: *mut PyObject) -> c_int {
+    let data = &(*(o as *mut PyDateTime_Time)).data;
+    ((data[3] as c_int) << 16) | ((data[4] as c_int) << 8) | data[5] as c_int
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_DELTA_GET_DAYS(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_Delta)).days
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_DELTA_GET_SECONDS(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_Delta)).seconds
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_DELTA_GET_MICROSECONDS(o: *mut PyObject) -> c_int {
+    (*(o as *mut PyDateTime_Delta)).microseconds
+}
+
+// Constructors
+
+#[inline(always)]
+pub unsafe fn PyDate_FromDate(year: c_int, month: c_int, day: c_int) -> *mut PyObject {
+    ((*PyDateTimeAPI).Date_FromDate)(year, month, day, (*PyDateTimeAPI).DateType)
+}
+
+#[inline(always)]
+pub unsafe fn PyDateTime_FromDateAndTime(year: c_int, month: c_int, day: c_int,
+                                         hour: c_int, minute: c_int, second: c_int,
+                                         usecond: c_int) -> *mut PyObject {
+    ((*PyDateTimeAPI).DateTime_FromDateAndTime)(year, month, day, hour, minute, second, usecond,
+                                                Py_None(), (*PyDateTimeAPI).DateTimeType)
+}
+
+#[inline(always)]
+pub unsafe fn PyTime_FromTime(hour: c_int, minute: c_int, second: c_int,
+                              usecond: c_int) -> *mut PyObject {
+    ((*PyDateTimeAPI).Time_FromTime)(hour, minute, second, usecond,
+                                     Py_None(), (*PyDateTimeAPI).TimeType)
+}
+
+#[inline(always)]
+pub unsafe fn PyDelta_FromDSU(days: c_int, seconds: c_int, useconds: c_int) -> *mut PyObject {
+    ((*PyDateTimeAPI).Delta_FromDelta)(days, seconds, useconds, 1, (*PyDateTimeAPI).DeltaType)
+}
+
+/// `args` is the argument tuple of `datetime.fromtimestamp`.
+#[inline(always)]
+pub unsafe fn PyDateTime_FromTimestamp(args: *mut PyObject) -> *mut PyObject {
+    ((*PyDateTimeAPI).DateTime_FromTimestamp)((*PyDateTimeAPI).DateTimeType as *mut PyObject,
+                                              args, 0 as *mut PyObject)
+}
+
+/// `args` is the argument tuple of `date.fromtimestamp`.
+#[inline(always)]
+pub unsafe fn PyDate_FromTimestamp(args: *mut PyObject) -> *mut PyObject {
+    ((*PyDateTimeAPI).Date_FromTimestamp)((*PyDateTimeAPI).DateType as *mut PyObject, args)
+}
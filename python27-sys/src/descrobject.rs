@@ -1,4 +1,5 @@
 use libc::{c_void, c_char, c_int};
+use pyport::Py_ssize_t;
 use object::{PyObject, PyTypeObject, Py_TYPE};
 use structmember::PyMemberDef;
 use methodobject::PyMethodDef;
@@ -51,6 +52,26 @@ impl Clone for wrapperbase {
     #[inline] fn clone(&self) -> wrapperbase { *self }
 }
 
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDescrObject {
+    #[cfg(py_sys_config="Py_TRACE_REFS")]
+    pub _ob_next: *mut PyObject,
+    #[cfg(py_sys_config="Py_TRACE_REFS")]
+    pub _ob_prev: *mut PyObject,
+    pub ob_refcnt: Py_ssize_t,
+    pub ob_type: *mut PyTypeObject,
+    pub d_type: *mut PyTypeObject,
+    pub d_name: *mut PyObject,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyMethodDescrObject {
+    pub d_common: PyDescrObject,
+    pub d_method: *mut PyMethodDef,
+}
+
 pub const PyWrapperFlag_KEYWORDS : c_int = 1;
 
 #[cfg_attr(windows, link(name="pythonXY"))] extern "C" {
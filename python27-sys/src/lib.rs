@@ -55,6 +55,7 @@ pub use compile::*;
 pub use eval::*;
 pub use structmember::PyMemberDef;
 pub use frameobject::PyFrameObject;
+pub use datetime::*;
 
 mod pyport;
 mod pymem;
@@ -125,6 +126,9 @@ mod eval;
 pub mod structmember;
 pub mod frameobject;
 
+// datetime.h is not included by Python.h
+mod datetime;
+
 pub const Py_single_input: libc::c_int = 256;
 pub const Py_file_input: libc::c_int = 257;
 pub const Py_eval_input: libc::c_int = 258;
@@ -1,8 +1,8 @@
-use libc::{c_void, c_char, c_int, c_long, c_ulong, c_longlong, c_ulonglong, c_double, size_t};
+use libc::{c_void, c_char, c_int, c_long, c_ulong, c_longlong, c_ulonglong, c_double, c_uchar, size_t};
 use pyport::Py_ssize_t;
 use object::*;
 
-//enum PyLongObject { /* representation hidden */ }
+pub enum PyLongObject { /* representation hidden */ }
 
 
 #[cfg_attr(windows, link(name="pythonXY"))] extern "C" {
@@ -56,12 +56,7 @@ pub unsafe fn PyLong_CheckExact(op : *mut PyObject) -> c_int {
     pub fn PyLong_AsVoidPtr(pylong: *mut PyObject) -> *mut c_void;
     
     pub fn PyLong_GetInfo() -> *mut PyObject;
-    
-    /*
-    pub fn _PyLong_AsInt(arg1: *mut PyObject) -> c_int;
-    pub fn _PyLong_Frexp(a: *mut PyLongObject, e: *mut Py_ssize_t)
-     -> c_double;
-    
+
     pub fn _PyLong_Sign(v: *mut PyObject) -> c_int;
     pub fn _PyLong_NumBits(v: *mut PyObject) -> size_t;
     pub fn _PyLong_FromByteArray(bytes: *const c_uchar, n: size_t,
@@ -71,6 +66,12 @@ pub unsafe fn PyLong_CheckExact(op : *mut PyObject) -> c_int {
                                bytes: *mut c_uchar, n: size_t,
                                little_endian: c_int,
                                is_signed: c_int) -> c_int;
+
+    /*
+    pub fn _PyLong_AsInt(arg1: *mut PyObject) -> c_int;
+    pub fn _PyLong_Frexp(a: *mut PyLongObject, e: *mut Py_ssize_t)
+     -> c_double;
+    
     pub fn _PyLong_Format(aa: *mut PyObject, base: c_int,
                           addL: c_int, newstyle: c_int)
      -> *mut PyObject;
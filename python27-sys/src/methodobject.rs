@@ -1,6 +1,6 @@
 use libc::{c_char, c_int};
 use core::ptr;
-//use pyport::Py_ssize_t;
+use pyport::Py_ssize_t;
 use object::{PyObject, PyTypeObject, Py_TYPE};
 
 #[cfg_attr(windows, link(name="pythonXY"))] extern "C" {
@@ -76,10 +76,9 @@ pub struct PyMethodChain {
     pub link: *mut PyMethodChain,
 }
 
-/*
 #[repr(C)]
-#[derive(Copy)]
-struct PyCFunctionObject {
+#[derive(Copy, Clone)]
+pub struct PyCFunctionObject {
     #[cfg(py_sys_config="Py_TRACE_REFS")]
     pub _ob_next: *mut PyObject,
     #[cfg(py_sys_config="Py_TRACE_REFS")]
@@ -90,7 +89,6 @@ struct PyCFunctionObject {
     pub m_self: *mut PyObject,
     pub m_module: *mut PyObject,
 }
-*/
 
 #[cfg_attr(windows, link(name="pythonXY"))] extern "C" {
     pub fn Py_FindMethod(methods: *mut PyMethodDef, slf: *mut PyObject,
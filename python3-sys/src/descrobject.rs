@@ -27,6 +27,24 @@ impl Clone for PyGetSetDef {
     #[inline] fn clone(&self) -> PyGetSetDef { *self }
 }
 
+#[cfg(not(Py_LIMITED_API))]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyDescrObject {
+    pub ob_base: PyObject,
+    pub d_type: *mut PyTypeObject,
+    pub d_name: *mut PyObject,
+    pub d_qualname: *mut PyObject,
+}
+
+#[cfg(not(Py_LIMITED_API))]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyMethodDescrObject {
+    pub d_common: PyDescrObject,
+    pub d_method: *mut PyMethodDef,
+}
+
 #[cfg_attr(windows, link(name="pythonXY"))] extern "C" {
     pub static mut PyClassMethodDescr_Type: PyTypeObject;
     pub static mut PyGetSetDescr_Type: PyTypeObject;
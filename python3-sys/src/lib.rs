@@ -70,6 +70,7 @@ pub use eval::*;
 
 pub use pystrtod::*;
 pub use frameobject::PyFrameObject;
+#[cfg(not(Py_LIMITED_API))] pub use datetime::*;
 
 mod pyport;
 // mod pymacro; contains nothing of interest for Rust
@@ -158,6 +159,9 @@ mod pystrtod; // TODO supports PEP-384 only; needs adjustment for Python 3.3 and
 // Additional headers that are not exported by Python.h
 pub mod structmember; // TODO supports PEP-384 only; needs adjustment for Python 3.3 and 3.5
 
+// datetime.h is not included by Python.h and not part of the stable ABI
+#[cfg(not(Py_LIMITED_API))] mod datetime;
+
 #[cfg(not(Py_LIMITED_API))]
 pub mod frameobject;
 #[cfg(Py_LIMITED_API)]
@@ -1,4 +1,4 @@
-use libc::{c_void, c_char, c_int, c_long, c_ulong, c_longlong, c_ulonglong, c_double, size_t};
+use libc::{c_void, c_char, c_int, c_long, c_ulong, c_longlong, c_ulonglong, c_double, c_uchar, size_t};
 use object::*;
 use pyport::Py_ssize_t;
 
@@ -58,3 +58,15 @@ pub unsafe fn PyLong_CheckExact(op : *mut PyObject) -> c_int {
      -> c_long;
 }
 
+#[cfg(not(Py_LIMITED_API))]
+#[cfg_attr(windows, link(name="pythonXY"))] extern "C" {
+    pub fn _PyLong_Sign(v: *mut PyObject) -> c_int;
+    pub fn _PyLong_NumBits(v: *mut PyObject) -> size_t;
+    pub fn _PyLong_FromByteArray(bytes: *const c_uchar, n: size_t,
+                                 little_endian: c_int,
+                                 is_signed: c_int) -> *mut PyObject;
+    pub fn _PyLong_AsByteArray(v: *mut PyLongObject,
+                               bytes: *mut c_uchar, n: size_t,
+                               little_endian: c_int,
+                               is_signed: c_int) -> c_int;
+}
@@ -55,6 +55,17 @@ impl Default for PyMethodDef {
     fn default() -> PyMethodDef { unsafe { mem::zeroed() } }
 }
 
+#[cfg(not(Py_LIMITED_API))]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct PyCFunctionObject {
+    pub ob_base: PyObject,
+    pub m_ml: *mut PyMethodDef,
+    pub m_self: *mut PyObject,
+    pub m_module: *mut PyObject,
+    pub m_weakreflist: *mut PyObject,
+}
+
 #[inline(always)]
 pub unsafe fn PyCFunction_New(ml: *mut PyMethodDef, slf: *mut PyObject) -> *mut PyObject {
     PyCFunction_NewEx(ml, slf, ptr::null_mut())
@@ -86,7 +97,11 @@ pub const METH_STATIC   : c_int = 0x0020;
 pub const METH_COEXIST   : c_int = 0x0040;
 
 #[cfg(all(Py_3_6, not(Py_LIMITED_API)))]
-pub const METHOD_FASTCALL : c_int = 0x0080;
+pub const METH_FASTCALL : c_int = 0x0080;
+
+// Misspelled name kept for backwards compatibility.
+#[cfg(all(Py_3_6, not(Py_LIMITED_API)))]
+pub const METHOD_FASTCALL : c_int = METH_FASTCALL;
 
 #[cfg_attr(windows, link(name="pythonXY"))] extern "C" {
     pub fn PyCFunction_ClearFreeList() -> c_int;
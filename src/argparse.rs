@@ -19,9 +19,11 @@
 //! This module contains logic for parsing a python argument list.
 //! See also the macros `py_argparse!`, `py_fn!` and `py_method!`.
 
-use std::ptr;
-use python::{Python, PythonObject};
+use std::{ptr, slice};
+use python::{Python, PythonObject, PyClone, ToPythonPointer};
 use objects::{PyObject, PyTuple, PyDict, PyString, exc};
+#[cfg(feature="python27-sys")]
+use objects::PyStringData;
 use conversion::{RefFromPyObject, ToPyObject};
 use ffi;
 use err::{self, PyResult};
@@ -31,7 +33,22 @@ pub struct ParamDescription<'a> {
     /// The name of the parameter.
     pub name: &'a str,
     /// Whether the parameter is optional.
-    pub is_optional: bool
+    pub is_optional: bool,
+    /// How the parameter can be passed to the function.
+    pub kind: ParamKind
+}
+
+/// Describes how an argument value can be bound to a parameter.
+/// Positional-only parameters must precede all other parameters,
+/// and keyword-only parameters must follow all other parameters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParamKind {
+    /// The parameter can only be passed by position (declared before `/`).
+    PositionalOnly,
+    /// The parameter can be passed either by position or by name.
+    PositionalOrKeyword,
+    /// The parameter can only be passed by name (declared after `*`).
+    KeywordOnly
 }
 
 /// Parse argument list
@@ -48,24 +65,175 @@ pub fn parse_args(
     args: &PyTuple, kwargs: Option<&PyDict>,
     output: &mut[Option<PyObject>]
 ) -> PyResult<()>
+{
+    parse_args_impl(py, fname, params, args.as_slice(py), kwargs, output)
+}
+
+/// Parse argument list passed using the `METH_FASTCALL` calling convention.
+///
+/// * fname:   Name of the current function
+/// * params:  Declared parameters of the function
+/// * args:    Positional arguments, followed by the values of the keyword arguments
+/// * kwnames: Names of the keyword arguments
+/// * output:  Output array that receives the arguments.
+///            Must have same length as `params` and must be initialized to `None`.
+pub fn parse_fastcall_args(
+    py: Python,
+    fname: Option<&str>, params: &[ParamDescription],
+    args: &[PyObject], kwnames: Option<&PyTuple>,
+    output: &mut[Option<PyObject>]
+) -> PyResult<()>
+{
+    let nkeywords = kwnames.map_or(0, |t| t.len(py));
+    assert!(nkeywords <= args.len());
+    let (args, values) = args.split_at(args.len() - nkeywords);
+    parse_args_impl(py, fname, params, args, FastCallKeywordArgs { names: kwnames, values: values }, output)
+}
+
+/// Abstracts over the representations of keyword arguments
+/// used by the different calling conventions.
+trait KeywordArgs {
+    fn len(&self, py: Python) -> usize;
+    fn get_item(&self, py: Python, name: &str) -> Option<PyObject>;
+    fn keys(&self, py: Python) -> Vec<PyObject>;
+}
+
+impl <'a> KeywordArgs for Option<&'a PyDict> {
+    fn len(&self, py: Python) -> usize {
+        self.map_or(0, |d| d.len(py))
+    }
+
+    fn get_item(&self, py: Python, name: &str) -> Option<PyObject> {
+        self.and_then(|d| d.get_item(py, name))
+    }
+
+    fn keys(&self, py: Python) -> Vec<PyObject> {
+        self.map_or(Vec::new(), |d| d.items(py).into_iter().map(|(key, _value)| key).collect())
+    }
+}
+
+/// Keyword arguments passed using the `METH_FASTCALL` calling convention:
+/// a tuple of names, and a slice with the corresponding values.
+struct FastCallKeywordArgs<'a> {
+    names: Option<&'a PyTuple>,
+    values: &'a [PyObject]
+}
+
+impl <'a> KeywordArgs for FastCallKeywordArgs<'a> {
+    fn len(&self, _py: Python) -> usize {
+        self.values.len()
+    }
+
+    fn get_item(&self, py: Python, name: &str) -> Option<PyObject> {
+        let names = match self.names {
+            Some(names) => names,
+            None => return None
+        };
+        for (key, value) in names.iter(py).zip(self.values) {
+            if keyword_matches(py, key, name) {
+                return Some(value.clone_ref(py));
+            }
+        }
+        None
+    }
+
+    fn keys(&self, py: Python) -> Vec<PyObject> {
+        self.names.map_or(Vec::new(), |names| names.iter(py).map(|key| key.clone_ref(py)).collect())
+    }
+}
+
+/// Compares a keyword argument name with a parameter name, without allocating.
+#[cfg(feature="python3-sys")]
+fn keyword_matches(_py: Python, key: &PyObject, name: &str) -> bool {
+    unsafe {
+        if ffi::PyUnicode_Check(key.as_ptr()) == 0 {
+            return false;
+        }
+        // The UTF-8 representation is cached in the string object
+        // (and is the string data itself for ASCII strings).
+        let mut size: ffi::Py_ssize_t = 0;
+        let data = ffi::PyUnicode_AsUTF8AndSize(key.as_ptr(), &mut size);
+        if data.is_null() {
+            // not encodable as UTF-8 (lone surrogates), so cannot match a Rust string
+            ffi::PyErr_Clear();
+            return false;
+        }
+        slice::from_raw_parts(data as *const u8, size as usize) == name.as_bytes()
+    }
+}
+
+/// Compares a keyword argument name with a parameter name, without allocating.
+#[cfg(feature="python27-sys")]
+fn keyword_matches(py: Python, key: &PyObject, name: &str) -> bool {
+    match key.cast_as::<PyString>(py) {
+        Ok(key) => match key.data(py) {
+            PyStringData::Utf8(data) => data == name.as_bytes(),
+            PyStringData::Latin1(data) => data.iter().map(|&b| b as u32).eq(name.chars().map(|c| c as u32)),
+            PyStringData::Utf16(data) => data.iter().cloned().eq(name.encode_utf16()),
+            PyStringData::Utf32(data) => data.iter().cloned().eq(name.chars().map(|c| c as u32))
+        },
+        Err(_) => false
+    }
+}
+
+fn parse_args_impl<K>(
+    py: Python,
+    fname: Option<&str>, params: &[ParamDescription],
+    args: &[PyObject], kwargs: K,
+    output: &mut[Option<PyObject>]
+) -> PyResult<()>
+    where K: KeywordArgs
 {
     assert!(params.len() == output.len());
-    let nargs = args.len(py);
-    let nkeywords = kwargs.map_or(0, |d| d.len(py));
+    let nargs = args.len();
+    let nkeywords = kwargs.len(py);
+    if nkeywords > 0 {
+        // like CPython, report positional-only parameters passed by name
+        // before checking the number of arguments
+        let names: Vec<&str> = params.iter()
+            .filter(|p| p.kind == ParamKind::PositionalOnly && kwargs.get_item(py, p.name).is_some())
+            .map(|p| p.name)
+            .collect();
+        if !names.is_empty() {
+            return Err(err::PyErr::new::<exc::TypeError, _>(py,
+                format!("{}{} got some positional-only arguments passed as keyword arguments: '{}'",
+                        fname.unwrap_or("function"),
+                        if fname.is_some() { "()" } else { "" },
+                        names.join(", ")
+                    )));
+        }
+    }
     if nargs + nkeywords > params.len() {
         return Err(err::PyErr::new::<exc::TypeError, _>(py,
-            format!("{}{} takes at most {} argument{} ({} given)",
+            format!("{}{} takes at most {} {}argument{} ({} given)",
                     fname.unwrap_or("function"),
                     if fname.is_some() { "()" } else { "" },
                     params.len(),
-                    if params.len() == 1 { "s" } else { "" },
+                    if nargs == 0 { "keyword " } else { "" },
+                    if params.len() == 1 { "" } else { "s" },
                     nargs + nkeywords
                 )));
     }
+    let npositional = params.iter().take_while(|p| p.kind != ParamKind::KeywordOnly).count();
+    if nargs > npositional {
+        return Err(err::PyErr::new::<exc::TypeError, _>(py,
+            format!("{}{} takes at most {} positional argument{} ({} given)",
+                    fname.unwrap_or("function"),
+                    if fname.is_some() { "()" } else { "" },
+                    npositional,
+                    if npositional == 1 { "" } else { "s" },
+                    nargs
+                )));
+    }
     let mut used_keywords = 0;
     // Iterate through the parameters and assign values to output:
     for (i, (p, out)) in params.iter().zip(output).enumerate() {
-        match kwargs.and_then(|d| d.get_item(py, p.name)) {
+        let kwarg = match p.kind {
+            // positional-only parameters never consume keyword arguments
+            ParamKind::PositionalOnly => None,
+            _ => kwargs.get_item(py, p.name)
+        };
+        match kwarg {
             Some(kwarg) => {
                 *out = Some(kwarg);
                 used_keywords += 1;
@@ -77,13 +245,11 @@ pub fn parse_args(
             },
             None => {
                 if i < nargs {
-                    *out = Some(args.get_item(py, i));
+                    *out = Some(args[i].clone_ref(py));
                 } else {
                     *out = None;
                     if !p.is_optional {
-                        return Err(err::PyErr::new::<exc::TypeError, _>(py,
-                            format!("Required argument ('{}') (pos {}) not found",
-                                    p.name, i+1)));
+                        return Err(missing_argument(py, fname, params, p, i, nargs));
                     }
                 }
             }
@@ -91,9 +257,9 @@ pub fn parse_args(
     }
     if used_keywords != nkeywords {
         // check for extraneous keyword arguments
-        for (key, _value) in kwargs.unwrap().items(py) {
+        for key in kwargs.keys(py) {
             let key = try!(try!(key.cast_as::<PyString>(py)).to_string(py));
-            if !params.iter().any(|p| p.name == key) {
+            if !params.iter().any(|p| p.kind != ParamKind::PositionalOnly && p.name == key) {
                 return Err(err::PyErr::new::<exc::TypeError, _>(py,
                     format!("'{}' is an invalid keyword argument for this function",
                             key)));
@@ -103,6 +269,30 @@ pub fn parse_args(
     Ok(())
 }
 
+fn missing_argument(
+    py: Python,
+    fname: Option<&str>, params: &[ParamDescription],
+    param: &ParamDescription, index: usize, nargs: usize
+) -> err::PyErr
+{
+    let msg = if param.kind == ParamKind::PositionalOnly {
+        let min = params.iter()
+            .filter(|p| p.kind == ParamKind::PositionalOnly && !p.is_optional)
+            .count();
+        let max = params.iter().filter(|p| p.kind != ParamKind::KeywordOnly).count();
+        format!("{}{} takes {} {} positional argument{} ({} given)",
+                fname.unwrap_or("function"),
+                if fname.is_some() { "()" } else { "" },
+                if min < max { "at least" } else { "exactly" },
+                min,
+                if min == 1 { "" } else { "s" },
+                nargs)
+    } else {
+        format!("Required argument ('{}') (pos {}) not found", param.name, index+1)
+    };
+    err::PyErr::new::<exc::TypeError, _>(py, msg)
+}
+
 /// This macro is used to parse a parameter list into a set of variables.
 ///
 /// Syntax: `py_argparse!(py, fname, args, kwargs, (parameter-list) { body })`
@@ -121,6 +311,12 @@ pub fn parse_args(
 ///    6. `**name`
 ///    7. `**name : ty`
 ///
+///   In addition, the parameter list may contain these markers:
+///    * `/`: all parameters declared before the `/` are positional-only,
+///      they cannot be passed as keyword arguments.
+///    * `*`: all parameters declared after the `*` are keyword-only,
+///      they cannot be passed as positional arguments.
+///
 ///   The types used must implement the `FromPyObject` trait.
 ///   If no type is specified, the parameter implicitly uses
 ///   `&PyObject` (format 1), `&PyTuple` (format 4) or `&PyDict` (format 6).
@@ -136,6 +332,7 @@ pub fn parse_args(
 ///
 /// The `py_argparse!()` macro special-cases reference types (when `ty` starts with a `&` token):
 /// In this case, the macro uses the `RefFromPyObject` trait instead of the `FromPyObject` trait.
+/// This allows parameters like `name: &str` or `data: &[u8]` to borrow from the Python object without copying.
 /// When using at least one reference parameter, the `body` block is placed within a closure,
 /// so `return` statements might behave unexpectedly in this case. (this only affects direct use
 /// of `py_argparse!`; `py_fn!` is unaffected as the body there is always in a separate function
@@ -154,6 +351,8 @@ macro_rules! py_argparse_parse_plist {
     // py_argparse_parse_plist! { callback { initial_args } (plist) }
     //  = callback! { initial_args [{ pname:ptype = [ {**} {default-value} ] } ...] }
     // The braces around the *s and the default-value are used even if they are empty.
+    // Instead of the *s, the first braces contain `/` for positional-only parameters
+    // and `=` for keyword-only parameters.
 
     // Special-case entry-point for empty parameter list:
     { $callback:ident { $($initial_arg:tt)* } ( ) } => {
@@ -176,6 +375,24 @@ macro_rules! py_argparse_parse_plist_impl {
     { $callback:ident { $($initial_arg:tt)* } $output:tt ( ) } => {
         $callback! { $($initial_arg)* $output }
     };
+    // Positional-only marker: turn all previous parameters into positional-only parameters
+    { $callback:ident $initial_args:tt [ $($output:tt)* ]
+        ( / , $($tail:tt)* )
+    } => {
+        py_argparse_set_param_kind! {
+            {/} $callback $initial_args [] [ $($output)* ] ($($tail)*)
+        }
+    };
+    // Keyword-only marker: parse the remaining parameters on their own,
+    // then turn them into keyword-only parameters
+    { $callback:ident $initial_args:tt $output:tt
+        ( * , $($tail:tt)* )
+    } => {
+        py_argparse_parse_plist_impl! {
+            py_argparse_set_param_kind { {=} $callback $initial_args $output }
+            [] ($($tail)*)
+        }
+    };
     // Kwargs parameter with reference extraction
     { $callback:ident $initial_args:tt [ $($output:tt)* ]
         ( ** $name:ident : &$t:ty , $($tail:tt)* )
@@ -288,6 +505,44 @@ macro_rules! py_argparse_parse_plist_impl {
     };
 }
 
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_argparse_set_param_kind {
+    // Helper for py_argparse_parse_plist_impl!: changes the kind of the parsed parameters
+    // in the second list from positional-or-keyword to $kind, appends them to the first list,
+    // and then continues parsing the remaining plist.
+    // `**kwargs` parameters are kept as they are.
+
+    // Base case: all parameters handled
+    { $kind:tt $callback:ident $initial_args:tt $output:tt [] $tail:tt } => {
+        py_argparse_parse_plist_impl! { $callback $initial_args $output $tail }
+    };
+    // Regular parameter
+    { $kind:tt $callback:ident $initial_args:tt [ $($output:tt)* ]
+        [ { $name:ident : $t:ty = [ {} $default:tt $rtype:tt ] } $($params:tt)* ] $tail:tt
+    } => {
+        py_argparse_set_param_kind! {
+            $kind $callback $initial_args
+            [ $($output)* { $name:$t = [ $kind $default $rtype ] } ]
+            [ $($params)* ] $tail
+        }
+    };
+    // Kwargs parameter
+    { $kind:tt $callback:ident $initial_args:tt [ $($output:tt)* ]
+        [ { $name:ident : $t:ty = [ {**} $default:tt $rtype:tt ] } $($params:tt)* ] $tail:tt
+    } => {
+        py_argparse_set_param_kind! {
+            $kind $callback $initial_args
+            [ $($output)* { $name:$t = [ {**} $default $rtype ] } ]
+            [ $($params)* ] $tail
+        }
+    };
+    // Entry point used as callback for py_argparse_parse_plist_impl!
+    { $kind:tt $callback:ident $initial_args:tt $output:tt $params:tt } => {
+        py_argparse_set_param_kind! { $kind $callback $initial_args $output $params () }
+    };
+}
+
 // The main py_argparse!() macro, except that it expects the parameter-list
 // in the output format of py_argparse_parse_plist!().
 #[macro_export]
@@ -360,25 +615,145 @@ pub unsafe fn get_kwargs(py: Python, ptr: *mut ffi::PyObject) -> Option<PyDict>
     }
 }
 
+// Like py_argparse_raw!(), but accepts the arguments of the `METH_FASTCALL` calling convention:
+// the argument array `*mut *mut ffi::PyObject`, the number of positional arguments,
+// and the tuple of keyword argument names.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_argparse_fastcall_raw {
+    ($py:ident, $fname:expr, $args:expr, $nargs:expr, $kwnames:expr, $plist:tt $body:block) => {{
+        let kwnames: Option<$crate::PyTuple> = $crate::argparse::get_kwnames($py, $kwnames);
+        let args: &[$crate::PyObject] = $crate::argparse::get_fastcall_args($py, $args, $nargs, kwnames.as_ref());
+        let ret = py_argparse_fastcall_impl!($py, $fname, args, kwnames.as_ref(), $body, $plist);
+        $crate::PyDrop::release_ref(kwnames, $py);
+        ret
+    }};
+}
+
+// Like py_argparse_impl!(), but expects the arguments of the `METH_FASTCALL` calling convention.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_argparse_fastcall_impl {
+    // special case: function signature is (*args, **kwargs),
+    // so we need to pack the arguments into a tuple and a dict.
+    ($py:expr, $fname:expr, $args:expr, $kwnames:expr, $body:block,
+        [
+            { $pargs:ident   : $pargs_type:ty   = [ {*}  {} {} ] }
+            { $pkwargs:ident : $pkwargs_type:ty = [ {**} {} {} ] }
+        ]
+    ) => {{
+        let py: $crate::Python = $py;
+        // Use `match` rather than `try!`, so that the caller can still release `$kwnames`.
+        match $crate::argparse::fastcall_args_to_tuple_dict(py, $args, $kwnames) {
+            Ok((args, kwargs)) => {
+                let ret = py_argparse_impl!(py, $fname, &args, kwargs.as_ref(), $body,
+                    [
+                        { $pargs   : $pargs_type   = [ {*}  {} {} ] }
+                        { $pkwargs : $pkwargs_type = [ {**} {} {} ] }
+                    ]);
+                $crate::PyDrop::release_ref(args, py);
+                $crate::PyDrop::release_ref(kwargs, py);
+                ret
+            },
+            Err(e) => Err(e)
+        }
+    }};
+
+    // normal argparse logic
+    ($py:expr, $fname:expr, $args:expr, $kwnames:expr, $body:block,
+        [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ]
+    ) => {{
+        const PARAMS: &'static [$crate::argparse::ParamDescription<'static>] = &[
+            $(
+                py_argparse_param_description! { $pname : $ptype = $detail }
+            ),*
+        ];
+        let py: $crate::Python = $py;
+        let mut output = [$( py_replace_expr!($pname None) ),*];
+        match $crate::argparse::parse_fastcall_args(py, $fname, PARAMS, $args, $kwnames, &mut output) {
+            Ok(()) => {
+                let mut _iter = output.iter();
+                py_argparse_extract!( py, _iter, $body,
+                    [ $( { $pname : $ptype = $detail } )* ])
+            },
+            Err(e) => Err(e)
+        }
+    }};
+}
+
+#[inline]
+#[doc(hidden)]
+pub unsafe fn get_kwnames(py: Python, ptr: *mut ffi::PyObject) -> Option<PyTuple> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(PyObject::from_borrowed_ptr(py, ptr).unchecked_cast_into())
+    }
+}
+
+/// Borrows the `METH_FASTCALL` argument array as a slice.
+/// The slice contains the positional arguments, followed by the values of the keyword arguments.
+#[inline]
+#[doc(hidden)]
+pub unsafe fn get_fastcall_args<'a>(
+    py: Python, args: *mut *mut ffi::PyObject, nargs: ffi::Py_ssize_t, kwnames: Option<&PyTuple>
+) -> &'a [PyObject]
+{
+    let len = nargs as usize + kwnames.map_or(0, |t| t.len(py));
+    if len == 0 {
+        // `args` may be null if there are no arguments
+        &[]
+    } else {
+        PyObject::borrow_from_owned_ptr_slice(slice::from_raw_parts(args, len))
+    }
+}
+
+#[doc(hidden)] // used in py_argparse_fastcall_impl!() macro
+pub fn fastcall_args_to_tuple_dict(
+    py: Python, args: &[PyObject], kwnames: Option<&PyTuple>
+) -> PyResult<(PyTuple, Option<PyDict>)>
+{
+    let kwnames = match kwnames {
+        Some(kwnames) => kwnames,
+        None => return Ok((PyTuple::new(py, args), None))
+    };
+    let (args, values) = args.split_at(args.len() - kwnames.len(py));
+    let kwargs = PyDict::new(py);
+    for (key, value) in kwnames.iter(py).zip(values) {
+        try!(kwargs.set_item(py, key, value));
+    }
+    Ok((PyTuple::new(py, args), Some(kwargs)))
+}
+
 #[macro_export]
 #[doc(hidden)]
 macro_rules! py_argparse_param_description {
     // normal parameter
-    { $pname:ident : $ptype:ty = [ {} {} $rtype:tt ] } => (
+    { $pname:ident : $ptype:ty = [ $kind:tt {} $rtype:tt ] } => (
         $crate::argparse::ParamDescription {
             name: stringify!($pname),
-            is_optional: false
+            is_optional: false,
+            kind: py_argparse_param_kind!($kind)
         }
     );
     // optional parameters
-    { $pname:ident : $ptype:ty = [ {} {$default:expr} {$($rtype:tt)*} ] } => (
+    { $pname:ident : $ptype:ty = [ $kind:tt {$default:expr} {$($rtype:tt)*} ] } => (
         $crate::argparse::ParamDescription {
             name: stringify!($pname),
-            is_optional: true
+            is_optional: true,
+            kind: py_argparse_param_kind!($kind)
         }
     );
 }
 
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_argparse_param_kind {
+    ({}) => ($crate::argparse::ParamKind::PositionalOrKeyword);
+    ({/}) => ($crate::argparse::ParamKind::PositionalOnly);
+    ({=}) => ($crate::argparse::ParamKind::KeywordOnly);
+}
+
 #[macro_export]
 #[doc(hidden)]
 macro_rules! py_argparse_extract {
@@ -386,7 +761,7 @@ macro_rules! py_argparse_extract {
     ( $py:expr, $iter:expr, $body:block, [] ) => { $body };
     // normal parameter
     ( $py:expr, $iter:expr, $body:block,
-        [ { $pname:ident : $ptype:ty = [ {} {} {} ] } $($tail:tt)* ]
+        [ { $pname:ident : $ptype:ty = [ {$($kind:tt)*} {} {} ] } $($tail:tt)* ]
     ) => {
         // First unwrap() asserts the iterated sequence is long enough (which should be guaranteed);
         // second unwrap() asserts the parameter was not missing (which fn parse_args already checked for).
@@ -397,7 +772,7 @@ macro_rules! py_argparse_extract {
     };
     // normal parameter with reference extraction
     ( $py:expr, $iter:expr, $body:block,
-        [ { $pname:ident : $ptype:ty = [ {} {} {$rtype:ty} ] } $($tail:tt)* ]
+        [ { $pname:ident : $ptype:ty = [ {$($kind:tt)*} {} {$rtype:ty} ] } $($tail:tt)* ]
     ) => {
         // First unwrap() asserts the iterated sequence is long enough (which should be guaranteed);
         // second unwrap() asserts the parameter was not missing (which fn parse_args already checked for).
@@ -411,7 +786,7 @@ macro_rules! py_argparse_extract {
     };
     // optional parameter
     ( $py:expr, $iter:expr, $body:block,
-        [ { $pname:ident : $ptype:ty = [ {} {$default:expr} {} ] } $($tail:tt)* ]
+        [ { $pname:ident : $ptype:ty = [ {$($kind:tt)*} {$default:expr} {} ] } $($tail:tt)* ]
     ) => {
         match $iter.next().unwrap().as_ref().map(|obj| obj.extract::<_>($py)).unwrap_or(Ok($default)) {
             Ok($pname) => py_argparse_extract!($py, $iter, $body, [$($tail)*]),
@@ -420,7 +795,7 @@ macro_rules! py_argparse_extract {
     };
     // optional parameter with reference extraction
     ( $py:expr, $iter:expr, $body:block,
-        [ { $pname:ident : $ptype:ty = [ {} {$default:expr} {$rtype:ty} ] } $($tail:tt)* ]
+        [ { $pname:ident : $ptype:ty = [ {$($kind:tt)*} {$default:expr} {$rtype:ty} ] } $($tail:tt)* ]
     ) => {
         //unwrap() asserts the iterated sequence is long enough (which should be guaranteed);
         $crate::argparse::with_extracted_or_default($py,
@@ -447,8 +822,9 @@ pub fn with_extracted_or_default<P: ?Sized, R, F>(py: Python, obj: Option<&PyObj
 #[cfg(test)]
 mod test {
     use python::{Python, PythonObject};
-    use objects::PyTuple;
+    use objects::{PyTuple, PyDict, exc};
     use conversion::ToPyObject;
+    use super::{ParamDescription, ParamKind, parse_fastcall_args};
 
     #[test]
     pub fn test_parse() {
@@ -503,4 +879,96 @@ mod test {
         }).unwrap();
         assert!(called);
     }
+
+    #[test]
+    pub fn test_keyword_only() {
+        let gil_guard = Python::acquire_gil();
+        let py = gil_guard.python();
+        let tuple = (1,).to_py_object(py);
+        let kwargs = PyDict::new(py);
+        kwargs.set_item(py, "flag", true).unwrap();
+        let r = py_argparse!(py, None, &tuple, Some(&kwargs), (x: i32, *, flag: bool = false) {
+            Ok((x, flag))
+        });
+        assert_eq!(r.unwrap(), (1, true));
+
+        let tuple = (1, true).to_py_object(py);
+        let r = py_argparse!(py, Some("f"), &tuple, None, (x: i32, *, flag: bool = false) {
+            Ok((x, flag))
+        });
+        let mut e = r.unwrap_err();
+        assert!(e.matches(py, py.get_type::<exc::TypeError>()));
+        assert_eq!(e.instance(py).to_string(), "f() takes at most 1 positional argument (2 given)");
+    }
+
+    #[test]
+    pub fn test_positional_only() {
+        let gil_guard = Python::acquire_gil();
+        let py = gil_guard.python();
+        let tuple = (1, 2).to_py_object(py);
+        let r = py_argparse!(py, None, &tuple, None, (x: i32, /, y: i32) {
+            Ok((x, y))
+        });
+        assert_eq!(r.unwrap(), (1, 2));
+
+        let tuple = PyTuple::new(py, &[]);
+        let kwargs = PyDict::new(py);
+        kwargs.set_item(py, "x", 1).unwrap();
+        kwargs.set_item(py, "y", 2).unwrap();
+        let r = py_argparse!(py, Some("f"), &tuple, Some(&kwargs), (x: i32, /, y: i32) {
+            Ok((x, y))
+        });
+        let mut e = r.unwrap_err();
+        assert!(e.matches(py, py.get_type::<exc::TypeError>()));
+        assert_eq!(e.instance(py).to_string(),
+                   "f() got some positional-only arguments passed as keyword arguments: 'x'");
+
+        let tuple = (1,).to_py_object(py);
+        let kwargs = PyDict::new(py);
+        kwargs.set_item(py, "x", 2).unwrap();
+        let r = py_argparse!(py, Some("f"), &tuple, Some(&kwargs), (x: i32, /) {
+            Ok(x)
+        });
+        let mut e = r.unwrap_err();
+        assert_eq!(e.instance(py).to_string(),
+                   "f() got some positional-only arguments passed as keyword arguments: 'x'");
+
+        let tuple = PyTuple::new(py, &[]);
+        let kwargs = PyDict::new(py);
+        kwargs.set_item(py, "x", 1).unwrap();
+        let r = py_argparse!(py, Some("f"), &tuple, Some(&kwargs), (x: i32, /, y: i32 = 0) {
+            Ok((x, y))
+        });
+        let mut e = r.unwrap_err();
+        assert_eq!(e.instance(py).to_string(),
+                   "f() got some positional-only arguments passed as keyword arguments: 'x'");
+
+        let tuple = PyTuple::new(py, &[]);
+        let r = py_argparse!(py, Some("f"), &tuple, None, (x: i32, /, y: i32 = 0) {
+            Ok((x, y))
+        });
+        let mut e = r.unwrap_err();
+        assert_eq!(e.instance(py).to_string(), "f() takes at least 1 positional argument (0 given)");
+    }
+
+    #[test]
+    pub fn test_fastcall_keywords() {
+        let gil_guard = Python::acquire_gil();
+        let py = gil_guard.python();
+        let params = [
+            ParamDescription { name: "x", is_optional: false, kind: ParamKind::PositionalOrKeyword },
+            ParamDescription { name: "y\u{e9}", is_optional: false, kind: ParamKind::PositionalOrKeyword },
+        ];
+        let args = [1i32.to_py_object(py).into_object(), 2i32.to_py_object(py).into_object()];
+        let kwnames = ("y\u{e9}", "x").to_py_object(py);
+        let mut output = [None, None];
+        parse_fastcall_args(py, None, &params, &args, Some(&kwnames), &mut output).unwrap();
+        assert_eq!(output[0].as_ref().unwrap().extract::<i32>(py).unwrap(), 2);
+        assert_eq!(output[1].as_ref().unwrap().extract::<i32>(py).unwrap(), 1);
+
+        let kwnames = ("z", "x").to_py_object(py);
+        let mut output = [None, None];
+        let e = parse_fastcall_args(py, None, &params, &args, Some(&kwnames), &mut output).unwrap_err();
+        assert!(e.matches(py, py.get_type::<exc::TypeError>()));
+    }
 }
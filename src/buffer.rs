@@ -23,7 +23,8 @@ use libc;
 use err::{self, PyResult};
 use exc;
 use python::{Python, PyDrop};
-use objects::PyObject;
+use conversion::RefFromPyObject;
+use objects::{PyObject, PyBytes};
 
 /// Allows access to the underlying buffer used by a python object such as `bytes`, `bytearray` or `array.array`.
 pub struct PyBuffer(Box<ffi::Py_buffer>); // use Box<> because Python expects that the Py_buffer struct has a stable memory address
@@ -564,6 +565,33 @@ impl_element!(isize, SignedInteger);
 impl_element!(f32, Float);
 impl_element!(f64, Float);
 
+/// Allows extracting byte slices from Python objects without copying.
+///
+/// `bytes` objects are borrowed directly; `bytearray` and other objects
+/// supporting the buffer protocol are accessed through a `PyBuffer`,
+/// which must be C-contiguous.
+/// While the slice is borrowed, the buffer exporter (e.g. a `bytearray`) can't be resized.
+impl RefFromPyObject for [u8] {
+    fn with_extracted<F, R>(py: Python, obj: &PyObject, f: F) -> PyResult<R>
+        where F: FnOnce(&[u8]) -> R
+    {
+        if let Ok(bytes) = obj.cast_as::<PyBytes>(py) {
+            return Ok(f(bytes.data(py)));
+        }
+        let buffer = PyBuffer::get(py, obj)?;
+        if !buffer.is_c_contiguous() {
+            buffer.release_ref(py);
+            return Err(err::PyErr::new::<exc::BufferError, _>(py, "Buffer is not C-contiguous."));
+        }
+        let result = {
+            let data = unsafe { slice::from_raw_parts(buffer.buf_ptr() as *const u8, buffer.len_bytes()) };
+            f(data)
+        };
+        buffer.release_ref(py);
+        Ok(result)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std;
@@ -571,6 +599,7 @@ mod test {
     use conversion::ToPyObject;
     use objects::{PySequence, PyList, PyTuple, PyIterator};
     use objectprotocol::ObjectProtocol;
+    use conversion::RefFromPyObject;
     use super::PyBuffer;
 
     #[test]
@@ -615,6 +644,30 @@ mod test {
         assert_eq!(buffer.to_vec::<u8>(py).unwrap(), b"abcde");
     }
 
+    #[test]
+    fn test_extract_byte_slice() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let bytes = py.eval("b'abc'", None, None).unwrap();
+        assert_eq!(<[u8]>::with_extracted(py, &bytes, |data| data.to_vec()).unwrap(), b"abc");
+        let bytearray = py.eval("bytearray(b'xyz')", None, None).unwrap();
+        assert_eq!(<[u8]>::with_extracted(py, &bytearray, |data| data.to_vec()).unwrap(), b"xyz");
+        // the buffer is released afterwards, so the bytearray can be resized again
+        bytearray.call_method(py, "append", (33,), None).unwrap();
+        assert!(<[u8]>::with_extracted(py, &py.None(), |_| ()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")] // memoryview slices with a step require python 3
+    fn test_extract_byte_slice_not_contiguous() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let view = py.eval("memoryview(b'abcdef')[::2]", None, None).unwrap();
+        assert!(<[u8]>::with_extracted(py, &view, |_| ()).is_err());
+        let view = py.eval("memoryview(b'abcdef')[1:4]", None, None).unwrap();
+        assert_eq!(<[u8]>::with_extracted(py, &view, |data| data.to_vec()).unwrap(), b"bcd");
+    }
+
     #[test]
     #[cfg(feature="python3-sys")] // array.array doesn't implement the buffer protocol in python 2.7
     fn test_array_buffer() {
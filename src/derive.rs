@@ -0,0 +1,158 @@
+// Copyright (c) 2017 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Runtime support for the code generated by `#[derive(ToPyObject, FromPyObject)]`
+//! (see the `cpython-derive` crate).
+
+use std::cell::RefCell;
+use std::sync::{Once, ONCE_INIT};
+use python::{Python, PythonObject, PyClone};
+use pythonrun::GILProtected;
+use conversion::FromPyObject;
+use err::{self, PyErr, PyResult};
+use objectprotocol::ObjectProtocol;
+use objects::{exc, PyObject, PyDict, PyList, PyString, PyTuple, NoArgs};
+
+/// Extracts the value of a field; on failure, the error message names the field.
+pub fn extract_field<T>(py: Python, value: &PyObject, container: &str, field: &str) -> PyResult<T>
+    where for<'a> T: FromPyObject<'a>
+{
+    value.extract(py).map_err(|e| err::add_context(py, e, &format!("failed to extract {}.{}", container, field)))
+}
+
+/// Gets `obj[key]`, or `None` if the key is missing.
+pub fn get_item(py: Python, obj: &PyObject, key: &str) -> PyResult<Option<PyObject>> {
+    if let Ok(dict) = obj.cast_as::<PyDict>(py) {
+        return Ok(dict.get_item(py, key));
+    }
+    match obj.get_item(py, key) {
+        Ok(value) => Ok(Some(value)),
+        Err(ref e) if e.matches(py, py.get_type::<exc::KeyError>()) => Ok(None),
+        Err(e) => Err(e)
+    }
+}
+
+/// Gets `obj.name`, or `None` if the attribute is missing.
+pub fn get_attr(py: Python, obj: &PyObject, name: &str) -> PyResult<Option<PyObject>> {
+    match obj.getattr(py, name) {
+        Ok(value) => Ok(Some(value)),
+        Err(ref e) if e.matches(py, py.get_type::<exc::AttributeError>()) => Ok(None),
+        Err(e) => Err(e)
+    }
+}
+
+pub fn missing_item(py: Python, container: &str, key: &str) -> PyErr {
+    PyErr::new::<exc::KeyError, _>(py, format!("missing key '{}' for {}", key, container))
+}
+
+pub fn missing_attr(py: Python, container: &str, name: &str) -> PyErr {
+    PyErr::new::<exc::AttributeError, _>(py, format!("missing attribute '{}' for {}", name, container))
+}
+
+/// Gets the items of a tuple (or list) with exactly `len` items.
+pub fn tuple_items(py: Python, obj: &PyObject, container: &str, len: usize) -> PyResult<Vec<PyObject>> {
+    let items: Vec<PyObject> = if let Ok(tuple) = obj.cast_as::<PyTuple>(py) {
+        tuple.as_slice(py).iter().map(|item| item.clone_ref(py)).collect()
+    } else if let Ok(list) = obj.cast_as::<PyList>(py) {
+        list.iter(py).collect()
+    } else {
+        return Err(PyErr::new::<exc::TypeError, _>(py, format!("expected a tuple for {}", container)));
+    };
+    if items.len() != len {
+        return Err(PyErr::new::<exc::ValueError, _>(py,
+            format!("expected a tuple of length {} for {}, got length {}", len, container, items.len())));
+    }
+    Ok(items)
+}
+
+/// Checks that `obj` is `None`, the representation of unit structs.
+pub fn unit_struct(py: Python, obj: &PyObject, container: &str) -> PyResult<()> {
+    if *obj == py.None() {
+        Ok(())
+    } else {
+        Err(PyErr::new::<exc::TypeError, _>(py, format!("expected None for {}", container)))
+    }
+}
+
+/// Checks that `obj` is the string `name`, the representation of unit enum variants.
+pub fn unit_variant(py: Python, obj: &PyObject, name: &str) -> PyResult<()> {
+    let matches = match obj.cast_as::<PyString>(py) {
+        Ok(s) => s.to_string_lossy(py) == name,
+        Err(_) => false
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(PyErr::new::<exc::ValueError, _>(py, format!("expected '{}'", name)))
+    }
+}
+
+/// Creates the error for an enum where none of the variants could be extracted.
+pub fn no_variant_matched(py: Python, container: &str, errors: Vec<(&str, PyErr)>) -> PyErr {
+    let mut message = format!("failed to extract {} from any variant", container);
+    for (variant, mut err) in errors {
+        let err_message = match err.instance(py).str(py) {
+            Ok(s) => s.to_string_lossy(py).into_owned(),
+            Err(_) => String::new()
+        };
+        message.push_str(&format!("; {}: {}", variant, err_message));
+    }
+    PyErr::new::<exc::TypeError, _>(py, message)
+}
+
+/// Sets `dict[key] = value`.
+///
+/// Panics if out of memory; string keys are always hashable.
+pub fn set_item(py: Python, dict: &PyDict, key: &str, value: PyObject) {
+    dict.set_item(py, key, value).unwrap();
+}
+
+#[cfg(feature="python3-sys")]
+const NAMESPACE: (&'static str, &'static str) = ("types", "SimpleNamespace");
+#[cfg(feature="python27-sys")]
+const NAMESPACE: (&'static str, &'static str) = ("argparse", "Namespace");
+
+static NAMESPACE_TYPE_INIT: Once = ONCE_INIT;
+static mut NAMESPACE_TYPE: *const GILProtected<RefCell<Option<PyObject>>> = 0 as *const _;
+
+/// Gets the namespace type, importing it on first use.
+fn namespace_type(py: Python) -> PyResult<PyObject> {
+    let cell = unsafe {
+        NAMESPACE_TYPE_INIT.call_once(|| {
+            NAMESPACE_TYPE = Box::into_raw(Box::new(GILProtected::new(RefCell::new(None))));
+        });
+        (*NAMESPACE_TYPE).get(py)
+    };
+    if let Some(ref namespace_type) = *cell.borrow() {
+        return Ok(namespace_type.clone_ref(py));
+    }
+    let namespace_type = try!(try!(py.import(NAMESPACE.0)).get(py, NAMESPACE.1));
+    *cell.borrow_mut() = Some(namespace_type.clone_ref(py));
+    Ok(namespace_type)
+}
+
+/// Creates a namespace object with the items of `dict` as attributes:
+/// a `types.SimpleNamespace` on Python 3, and an `argparse.Namespace`
+/// on Python 2 (which has no `SimpleNamespace`).
+///
+/// Panics if the module defining the namespace type cannot be imported.
+pub fn namespace(py: Python, dict: PyDict) -> PyObject {
+    namespace_type(py)
+        .and_then(|namespace_type| namespace_type.call(py, NoArgs, Some(&dict)))
+        .unwrap_or_else(|e| panic!("Failed to create {}.{}: {:?}", NAMESPACE.0, NAMESPACE.1, e))
+}
@@ -19,7 +19,7 @@
 use std;
 use python::{PythonObject, ToPythonPointer, Python, PythonObjectDowncastError,
         PythonObjectWithTypeObject, PyClone, PyDrop};
-use objects::{PyObject, PyType, exc};
+use objects::{PyObject, PyType, PyTraceback, exc};
 #[cfg(feature="python27-sys")]
 use objects::oldstyle::PyClass;
 use ffi;
@@ -27,6 +27,7 @@ use libc;
 use std::ptr;
 use libc::c_char;
 use conversion::ToPyObject;
+use objectprotocol::ObjectProtocol;
 use std::ffi::CString;
 
 /**
@@ -349,6 +350,25 @@ impl PyErr {
         }
     }
 
+    /// Retrieves the traceback of this error, if any.
+    pub fn traceback(&self, py: Python) -> Option<PyTraceback> {
+        match self.ptraceback {
+            Some(ref tb) => tb.cast_as::<PyTraceback>(py).ok().map(|tb| tb.clone_ref(py)),
+            None => None
+        }
+    }
+
+    /// Formats the error and its traceback like Python's `traceback.format_exception()`.
+    ///
+    /// Unlike `print()`, this allows the traceback to be logged elsewhere than `sys.stderr`.
+    pub fn format_traceback(&self, py: Python) -> PyResult<String> {
+        let PyErr { ptype, pvalue, ptraceback } = self.clone_ref(py).into_normalized(py);
+        let traceback = try!(py.import("traceback"));
+        let lines = try!(traceback.call(py, "format_exception", (ptype, pvalue, ptraceback), None));
+        let lines: Vec<String> = try!(lines.extract(py));
+        Ok(lines.concat())
+    }
+
     /// Writes the error back to the Python interpreter's global state.
     /// This is the opposite of `PyErr::fetch()`.
     #[inline]
@@ -440,6 +460,51 @@ pub unsafe fn cast_from_owned_ptr_or_panic<T>(py : Python, p : *mut ffi::PyObjec
     }
 }
 
+/// Adds context to an error that occurred while converting part of a larger value.
+///
+/// The message of the returned error is `"{context}: {message}"`,
+/// and the original exception is its `__cause__`.
+/// `TypeError`, `ValueError` and `OverflowError` keep their type and traceback.
+/// Other exceptions are replaced by a `TypeError` (for subclasses of `TypeError`)
+/// or a `ValueError`, because their constructors may not accept a single message.
+pub fn add_context(py: Python, mut err: PyErr, context: &str) -> PyErr {
+    let instance = err.instance(py);
+    let message = match instance.str(py) {
+        Ok(s) => format!("{}: {}", context, s.to_string_lossy(py)),
+        Err(_) => format!("{}: <unprintable {} object>", context, err.get_type(py).name(py))
+    };
+    let ptype = context_exception_type(py, &err);
+    let new_instance = match ptype.call(py, (message,), None) {
+        Ok(obj) => obj,
+        Err(_) => return err
+    };
+    if new_instance.setattr(py, "__cause__", instance).is_err() {
+        return err;
+    }
+    PyErr {
+        ptype: ptype.into_object(),
+        pvalue: Some(new_instance),
+        ptraceback: err.ptraceback.clone_ref(py)
+    }
+}
+
+/// Gets the built-in exception type used by `add_context` for `err`.
+fn context_exception_type(py: Python, err: &PyErr) -> PyType {
+    let kept = [py.get_type::<exc::TypeError>(),
+                py.get_type::<exc::ValueError>(),
+                py.get_type::<exc::OverflowError>()];
+    for ty in kept.iter() {
+        if err.ptype.as_ptr() == ty.as_object().as_ptr() {
+            return ty.clone_ref(py);
+        }
+    }
+    if err.matches(py, py.get_type::<exc::TypeError>()) {
+        py.get_type::<exc::TypeError>()
+    } else {
+        py.get_type::<exc::ValueError>()
+    }
+}
+
 /// Returns Ok if the error code is not -1.
 #[inline]
 pub fn error_on_minusone(py : Python, result : libc::c_int) -> PyResult<()> {
@@ -453,8 +518,48 @@ pub fn error_on_minusone(py : Python, result : libc::c_int) -> PyResult<()> {
 #[cfg(test)]
 mod tests {
     use {Python, PyErr};
+    use objectprotocol::ObjectProtocol;
     use objects::exc;
 
+    #[test]
+    fn add_context_keeps_type_and_cause() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let err = py.run("def f():\n    raise ValueError('bad')\nf()\n", None, None).unwrap_err();
+        let mut err = super::add_context(py, err, "field x");
+        assert!(err.matches(py, py.get_type::<exc::ValueError>()));
+        let instance = err.instance(py);
+        assert_eq!(instance.to_string(), "field x: bad");
+        let cause = instance.getattr(py, "__cause__").unwrap();
+        assert_eq!(cause.to_string(), "bad");
+        assert!(err.traceback(py).is_some());
+    }
+
+    #[test]
+    fn add_context_replaces_other_exceptions() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let err = py.run("b'\\xff'.decode('utf-8')", None, None).unwrap_err();
+        let mut err = super::add_context(py, err, "field x");
+        assert!(err.matches(py, py.get_type::<exc::ValueError>()));
+        assert!(!err.matches(py, py.get_type::<exc::UnicodeDecodeError>()));
+        let instance = err.instance(py);
+        let message = instance.to_string();
+        assert!(message.starts_with("field x: ") && message.contains("codec can't decode"), "{}", message);
+        let cause = instance.getattr(py, "__cause__").unwrap();
+        assert!(cause.get_type(py) == py.get_type::<exc::UnicodeDecodeError>());
+
+        let err = py.run("{}['k']", None, None).unwrap_err();
+        let mut err = super::add_context(py, err, "field x");
+        assert!(err.matches(py, py.get_type::<exc::ValueError>()));
+        assert_eq!(err.instance(py).to_string(), "field x: 'k'");
+
+        let err = py.run("class E(TypeError):\n    def __init__(self, a, b): TypeError.__init__(self, a + b)\nraise E('a', 'b')", None, None).unwrap_err();
+        let mut err = super::add_context(py, err, "field x");
+        assert!(err.get_type(py) == py.get_type::<exc::TypeError>());
+        assert_eq!(err.instance(py).to_string(), "field x: ab");
+    }
+
     #[test]
     fn set_typeerror() {
         let gil = Python::acquire_gil();
@@ -43,14 +43,66 @@ macro_rules! py_method_def {
                                   $crate::_detail::ffi::PyCFunction>($wrap)
         );
         &mut METHOD_DEF
-    }}
+    }};
+    // Variant that also provides a wrapper for the `METH_FASTCALL` calling convention,
+    // which is used instead of `$wrap` if the Python version supports it.
+    ($name: expr, $flags: expr, $wrap: expr, $wrap_fast: expr) => {{
+        static mut METHOD_DEF: $crate::_detail::ffi::PyMethodDef = $crate::_detail::ffi::PyMethodDef {
+            ml_name: 0 as *const $crate::_detail::libc::c_char,
+            ml_meth: None,
+            ml_flags: 0,
+            ml_doc: 0 as *const $crate::_detail::libc::c_char
+        };
+        METHOD_DEF.ml_name = concat!($name, "\0").as_ptr() as *const _;
+        $crate::_detail::set_method_def_meth(&mut METHOD_DEF, $flags, $wrap, $wrap_fast);
+        &mut METHOD_DEF
+    }};
+}
+
+/// Signature of functions using the `METH_FASTCALL` calling convention
+/// (`METH_FASTCALL | METH_KEYWORDS` in Python 3.7 and later).
+pub type PyCFunctionFast =
+    unsafe extern "C" fn(slf: *mut ffi::PyObject, args: *mut *mut ffi::PyObject,
+                         nargs: ffi::Py_ssize_t, kwnames: *mut ffi::PyObject)
+                         -> *mut ffi::PyObject;
+
+// In Python 3.6, METH_FASTCALL always passes the keyword argument names.
+#[cfg(all(Py_3_6, not(Py_3_7), not(Py_LIMITED_API)))]
+const METH_FASTCALL_KEYWORDS: libc::c_int = ffi::METH_FASTCALL;
+
+#[cfg(all(Py_3_7, not(Py_LIMITED_API)))]
+const METH_FASTCALL_KEYWORDS: libc::c_int = ffi::METH_FASTCALL | ffi::METH_KEYWORDS;
+
+/// Sets the method and flags of `def`, using the `METH_FASTCALL` calling convention
+/// if it is supported by the Python version.
+///
+/// Python 3.8 and later also invoke `METH_FASTCALL` functions using
+/// the vectorcall protocol, avoiding the creation of argument tuples entirely.
+#[cfg(all(Py_3_6, not(Py_LIMITED_API)))]
+pub unsafe fn set_method_def_meth(
+    def: &mut ffi::PyMethodDef, flags: libc::c_int,
+    _wrap: ffi::PyCFunctionWithKeywords, wrap_fast: PyCFunctionFast
+) {
+    def.ml_flags = METH_FASTCALL_KEYWORDS | flags;
+    def.ml_meth = Some(mem::transmute::<PyCFunctionFast, ffi::PyCFunction>(wrap_fast));
+}
+
+/// Sets the method and flags of `def`, using the `METH_FASTCALL` calling convention
+/// if it is supported by the Python version.
+#[cfg(not(all(Py_3_6, not(Py_LIMITED_API))))]
+pub unsafe fn set_method_def_meth(
+    def: &mut ffi::PyMethodDef, flags: libc::c_int,
+    wrap: ffi::PyCFunctionWithKeywords, _wrap_fast: PyCFunctionFast
+) {
+    def.ml_flags = ffi::METH_VARARGS | ffi::METH_KEYWORDS | flags;
+    def.ml_meth = Some(mem::transmute::<ffi::PyCFunctionWithKeywords, ffi::PyCFunction>(wrap));
 }
 
 /// Creates a Python callable object that invokes a Rust function.
 ///
 /// There are two forms of this macro:
 ///
-/// 1. `py_fn!(py, f(parameter_list))`
+/// 1. `py_fn!(py, f(parameter_list))` or `py_fn!(py, f(parameter_list) -> PyResult<T>)`
 /// 1. `py_fn!(py, f(parameter_list) -> PyResult<T> { body })`
 ///
 /// both forms return a value of type `PyObject`.
@@ -67,6 +119,8 @@ macro_rules! py_method_def {
 /// * `f` must be the name of a function that is compatible with the specified
 ///    parameter list, except that a single parameter of type `Python` is prepended.
 ///    The function must return `PyResult<T>` for some `T` that implements `ToPyObject`.
+/// * The return type is optional; if it is specified, it must match the return type of `f`.
+///   It is only used for generating type stubs (see the `stubs` module).
 ///
 /// Form 2:
 ///
@@ -100,18 +154,48 @@ macro_rules! py_method_def {
 #[macro_export]
 macro_rules! py_fn {
     ($py:expr, $f:ident $plist:tt ) => {
-        py_argparse_parse_plist! { py_fn_impl { $py, $f } $plist }
+        py_argparse_parse_plist! { py_fn_impl { $py, $f, {} } $plist }
     };
     ($py:ident, $f:ident $plist:tt -> $ret:ty { $($body:tt)* } ) => {
         py_argparse_parse_plist! { py_fn_impl { $py, $f, $ret, { $($body)* } } $plist }
     };
+    ($py:expr, $f:ident $plist:tt -> $ret:ty ) => {
+        py_argparse_parse_plist! { py_fn_impl { $py, $f, { $ret } } $plist }
+    };
+}
+
+// Records the signature of a function created by `py_fn!()` or `py_class!()`
+// for generating type stubs. Does nothing unless the `stubs` feature is enabled.
+// `$ret` is `{}` if the return type is unknown, or `{ type }`.
+#[cfg(feature="stubs")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_register_signature {
+    ($py:expr, $key:expr, $name:expr, $ret:tt, $params:tt) => {
+        $crate::stubs::register_signature($py, $key as *const _ as *const _, || py_fn_signature!($name, $ret, $params))
+    };
+}
+
+#[cfg(not(feature="stubs"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_register_signature {
+    ($py:expr, $key:expr, $name:expr, $ret:tt, $params:tt) => {};
+}
+
+// Calls `f`; checks the return type if it is specified.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_fn_call {
+    ({}, $call:expr) => { $call };
+    ({ $ret:ty }, $call:expr) => {{ let ret: $ret = $call; ret }};
 }
 
 #[macro_export]
 #[doc(hidden)]
 macro_rules! py_fn_impl {
     // Form 1: reference existing function
-    { $py:expr, $f:ident [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ] } => {{
+    { $py:expr, $f:ident, $ret:tt [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ] } => {{
         unsafe extern "C" fn wrap(
             _slf: *mut $crate::_detail::ffi::PyObject,
             args: *mut $crate::_detail::ffi::PyObject,
@@ -124,19 +208,37 @@ macro_rules! py_fn_impl {
                     py_argparse_raw!(py, Some(stringify!($f)), args, kwargs,
                         [ $( { $pname : $ptype = $detail } )* ]
                         {
-                            $f(py $(, $pname )* )
+                            py_fn_call!($ret, $f(py $(, $pname )* ))
                         })
                 })
         }
-        unsafe {
-            $crate::_detail::py_fn_impl($py,
-                py_method_def!(stringify!($f), 0, wrap))
+        unsafe extern "C" fn wrap_fast(
+            _slf: *mut $crate::_detail::ffi::PyObject,
+            args: *mut *mut $crate::_detail::ffi::PyObject,
+            nargs: $crate::_detail::ffi::Py_ssize_t,
+            kwnames: *mut $crate::_detail::ffi::PyObject)
+        -> *mut $crate::_detail::ffi::PyObject
+        {
+            $crate::_detail::handle_callback(
+                stringify!($f), $crate::_detail::PyObjectCallbackConverter,
+                |py| {
+                    py_argparse_fastcall_raw!(py, Some(stringify!($f)), args, nargs, kwnames,
+                        [ $( { $pname : $ptype = $detail } )* ]
+                        {
+                            py_fn_call!($ret, $f(py $(, $pname )* ))
+                        })
+                })
         }
+        let py: $crate::Python = $py;
+        let method_def = unsafe { py_method_def!(stringify!($f), 0, wrap, wrap_fast) };
+        py_register_signature!(py, method_def, stringify!($f), $ret,
+            [ $( { $pname : $ptype = $detail } )* ]);
+        unsafe { $crate::_detail::py_fn_impl(py, method_def) }
     }};
     // Form 2: inline function definition
     { $py:ident, $f:ident, $ret:ty, $body:block [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ] } => {{
         fn $f($py: $crate::Python $( , $pname : $ptype )* ) -> $ret $body
-        py_fn_impl!($py, $f [ $( { $pname : $ptype = $detail } )* ])
+        py_fn_impl!($py, $f, { $ret } [ $( { $pname : $ptype = $detail } )* ])
     }}
 }
 
@@ -86,13 +86,17 @@ extern crate python27_sys as ffi;
 #[cfg(feature="python3-sys")]
 extern crate python3_sys as ffi;
 
+#[cfg(all(test, feature="serde"))]
+#[macro_use]
+extern crate serde_derive;
+
 pub use ffi::Py_ssize_t;
 pub use err::{PyErr, PyResult};
 pub use objects::*;
 pub use python::{Python, PythonObject, PythonObjectWithCheckedDowncast, PythonObjectDowncastError, PythonObjectWithTypeObject, PyClone, PyDrop};
 pub use pythonrun::{GILGuard, GILProtected, prepare_freethreaded_python};
 pub use conversion::{FromPyObject, RefFromPyObject, ToPyObject};
-pub use py_class::{CompareOp};
+pub use py_class::{CompareOp, PyRustIterator};
 pub use objectprotocol::{ObjectProtocol};
 
 #[cfg(feature="python27-sys")]
@@ -180,9 +184,14 @@ mod conversion;
 mod objects;
 mod objectprotocol;
 mod pythonrun;
-pub mod argparse;
-mod function;
+#[macro_use] pub mod argparse;
+#[cfg(feature="stubs")]
+#[macro_use] pub mod stubs;
+#[macro_use] mod function;
+mod derive;
 pub mod buffer;
+#[cfg(feature="serde")]
+pub mod serde;
 //pub mod rustobject;
 pub mod py_class;
 
@@ -196,8 +205,13 @@ pub mod _detail {
         pub use ::libc::{c_char, c_void, c_int};
     }
     pub use err::{from_owned_ptr_or_panic, result_from_owned_ptr};
+    pub use objects::get_interned;
     pub use function::{handle_callback, py_fn_impl, AbortOnDrop,
-        PyObjectCallbackConverter, PythonObjectCallbackConverter};
+        PyObjectCallbackConverter, PythonObjectCallbackConverter,
+        PyCFunctionFast, set_method_def_meth};
+    pub mod derive {
+        pub use ::derive::*;
+    }
 }
 
 /// Expands to an `extern "C"` function that allows Python to load
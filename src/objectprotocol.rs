@@ -21,9 +21,10 @@ use std::cmp::Ordering;
 use ffi;
 use libc;
 use python::{Python, PythonObject, ToPythonPointer};
-use objects::{PyObject, PyTuple, PyDict, PyString};
+use objects::{PyObject, PyTuple, PyDict, PyString, PyList, PyType};
 use conversion::ToPyObject;
 use err::{PyErr, PyResult, self};
+use py_class::CompareOp;
 
 /// Trait that contains methods 
 pub trait ObjectProtocol : PythonObject {
@@ -87,6 +88,97 @@ pub trait ObjectProtocol : PythonObject {
         })
     }
 
+    /// Compares two Python objects using the rich comparison operator `op`.
+    /// This is equivalent to the Python expressions 'self < other', 'self == other' etc.
+    ///
+    /// The result is not necessarily a `bool`; e.g. numpy arrays compare element-wise.
+    #[inline]
+    fn rich_compare<O>(&self, py: Python, other: O, op: CompareOp) -> PyResult<PyObject> where O: ToPyObject {
+        other.with_borrowed_ptr(py, |other| unsafe {
+            err::result_from_owned_ptr(py,
+                ffi::PyObject_RichCompare(self.as_ptr(), other, op as libc::c_int))
+        })
+    }
+
+    /// Tests whether `self == other`, converting the result to `bool`.
+    ///
+    /// Like Python containers, this considers identical objects to be equal.
+    /// (The method is not called `eq` to avoid conflicts with `PartialEq::eq`.)
+    #[inline]
+    fn rich_eq<O>(&self, py: Python, other: O) -> PyResult<bool> where O: ToPyObject {
+        rich_compare_bool(py, self.as_object(), other, CompareOp::Eq)
+    }
+
+    /// Tests whether `self != other`, converting the result to `bool`.
+    #[inline]
+    fn rich_ne<O>(&self, py: Python, other: O) -> PyResult<bool> where O: ToPyObject {
+        rich_compare_bool(py, self.as_object(), other, CompareOp::Ne)
+    }
+
+    /// Tests whether `self < other`, converting the result to `bool`.
+    #[inline]
+    fn rich_lt<O>(&self, py: Python, other: O) -> PyResult<bool> where O: ToPyObject {
+        rich_compare_bool(py, self.as_object(), other, CompareOp::Lt)
+    }
+
+    /// Tests whether `self <= other`, converting the result to `bool`.
+    #[inline]
+    fn rich_le<O>(&self, py: Python, other: O) -> PyResult<bool> where O: ToPyObject {
+        rich_compare_bool(py, self.as_object(), other, CompareOp::Le)
+    }
+
+    /// Tests whether `self > other`, converting the result to `bool`.
+    #[inline]
+    fn rich_gt<O>(&self, py: Python, other: O) -> PyResult<bool> where O: ToPyObject {
+        rich_compare_bool(py, self.as_object(), other, CompareOp::Gt)
+    }
+
+    /// Tests whether `self >= other`, converting the result to `bool`.
+    #[inline]
+    fn rich_ge<O>(&self, py: Python, other: O) -> PyResult<bool> where O: ToPyObject {
+        rich_compare_bool(py, self.as_object(), other, CompareOp::Ge)
+    }
+
+    /// Determines whether this object is an instance of `ty` (or of a subclass).
+    /// This is equivalent to the Python expression 'isinstance(self, ty)'.
+    #[inline]
+    fn is_instance(&self, py: Python, ty: &PyType) -> PyResult<bool> {
+        let result = unsafe { ffi::PyObject_IsInstance(self.as_ptr(), ty.as_ptr()) };
+        if result == -1 {
+            Err(PyErr::fetch(py))
+        } else {
+            Ok(result != 0)
+        }
+    }
+
+    /// Determines whether this object is a class derived from `ty`.
+    /// This is equivalent to the Python expression 'issubclass(self, ty)'.
+    #[inline]
+    fn is_subclass(&self, py: Python, ty: &PyType) -> PyResult<bool> {
+        let result = unsafe { ffi::PyObject_IsSubclass(self.as_ptr(), ty.as_ptr()) };
+        if result == -1 {
+            Err(PyErr::fetch(py))
+        } else {
+            Ok(result != 0)
+        }
+    }
+
+    /// Lists the names of the attributes of this object.
+    /// This is equivalent to the Python expression 'dir(self)'.
+    #[inline]
+    fn dir(&self, py: Python) -> PyResult<PyList> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyObject_Dir(self.as_ptr()))
+        }
+    }
+
+    /// Returns the identity of this object.
+    /// This is equivalent to the Python expression 'id(self)'.
+    #[inline]
+    fn id(&self, _py: Python) -> usize {
+        self.as_ptr() as usize
+    }
+
     /// Compute the string representation of self.
     /// This is equivalent to the Python expression 'repr(self)'.
     #[inline]
@@ -136,9 +228,12 @@ pub trait ObjectProtocol : PythonObject {
 
     /// Calls a method on the object.
     /// This is equivalent to the Python expression: 'self.name(*args, **kwargs)'
+    ///
+    /// `name` is usually a `&str`; pass a `PyString` (e.g. from `py_intern!`)
+    /// to avoid converting the name on every call.
     #[inline]
-    fn call_method<A>(&self, py: Python, name: &str, args: A, kwargs: Option<&PyDict>) -> PyResult<PyObject>
-        where A: ToPyObject<ObjectType=PyTuple>
+    fn call_method<N, A>(&self, py: Python, name: N, args: A, kwargs: Option<&PyDict>) -> PyResult<PyObject>
+        where N: ToPyObject, A: ToPyObject<ObjectType=PyTuple>
     {
         try!(self.getattr(py, name)).call(py, args, kwargs)
     }
@@ -219,10 +314,225 @@ pub trait ObjectProtocol : PythonObject {
         });
         Ok(try!(::objects::PyIterator::from_object(py, obj)))
     }
+
+    // Number protocol.
+    // Unlike calling `__add__` etc. by name, these methods fall back to the
+    // reflected operation of `other` (e.g. `__radd__`), just like the Python operators.
+
+    /// This is equivalent to the Python expression 'self + other'.
+    #[inline]
+    fn add<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_Add)
+    }
+
+    /// This is equivalent to the Python expression 'self - other'.
+    #[inline]
+    fn sub<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_Subtract)
+    }
+
+    /// This is equivalent to the Python expression 'self * other'.
+    #[inline]
+    fn mul<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_Multiply)
+    }
+
+    /// This is equivalent to the Python expression 'self / other'.
+    #[inline]
+    fn true_div<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_TrueDivide)
+    }
+
+    /// This is equivalent to the Python expression 'self // other'.
+    #[inline]
+    fn floor_div<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_FloorDivide)
+    }
+
+    /// This is equivalent to the Python expression 'self % other'.
+    #[inline]
+    fn rem<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_Remainder)
+    }
+
+    /// This is equivalent to the Python expression 'self ** other'.
+    #[inline]
+    fn pow<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, number_power)
+    }
+
+    /// This is equivalent to the Python expression 'self << other'.
+    #[inline]
+    fn lshift<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_Lshift)
+    }
+
+    /// This is equivalent to the Python expression 'self >> other'.
+    #[inline]
+    fn rshift<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_Rshift)
+    }
+
+    /// This is equivalent to the Python expression 'self & other'.
+    #[inline]
+    fn bitand<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_And)
+    }
+
+    /// This is equivalent to the Python expression 'self | other'.
+    #[inline]
+    fn bitor<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_Or)
+    }
+
+    /// This is equivalent to the Python expression 'self ^ other'.
+    #[inline]
+    fn bitxor<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_Xor)
+    }
+
+    /// This is equivalent to the Python expression 'divmod(self, other)'.
+    #[inline]
+    fn divmod<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_Divmod)
+    }
+
+    /// This is equivalent to the Python expression '-self'.
+    #[inline]
+    fn neg(&self, py: Python) -> PyResult<PyObject> {
+        number_unary_op(py, self.as_object(), ffi::PyNumber_Negative)
+    }
+
+    /// This is equivalent to the Python expression '+self'.
+    #[inline]
+    fn pos(&self, py: Python) -> PyResult<PyObject> {
+        number_unary_op(py, self.as_object(), ffi::PyNumber_Positive)
+    }
+
+    /// This is equivalent to the Python expression 'abs(self)'.
+    #[inline]
+    fn abs(&self, py: Python) -> PyResult<PyObject> {
+        number_unary_op(py, self.as_object(), ffi::PyNumber_Absolute)
+    }
+
+    /// This is equivalent to the Python expression '~self'.
+    #[inline]
+    fn invert(&self, py: Python) -> PyResult<PyObject> {
+        number_unary_op(py, self.as_object(), ffi::PyNumber_Invert)
+    }
+
+    /// This is equivalent to the Python statement 'self += other'.
+    ///
+    /// The operation is performed in-place if `self` supports it (e.g. lists);
+    /// otherwise, this behaves like the corresponding binary operation.
+    /// Either way, the result is returned and should be used in place of `self`.
+    #[inline]
+    fn inplace_add<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_InPlaceAdd)
+    }
+
+    /// This is equivalent to the Python statement 'self -= other'.
+    #[inline]
+    fn inplace_sub<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_InPlaceSubtract)
+    }
+
+    /// This is equivalent to the Python statement 'self *= other'.
+    #[inline]
+    fn inplace_mul<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_InPlaceMultiply)
+    }
+
+    /// This is equivalent to the Python statement 'self /= other'.
+    #[inline]
+    fn inplace_true_div<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_InPlaceTrueDivide)
+    }
+
+    /// This is equivalent to the Python statement 'self //= other'.
+    #[inline]
+    fn inplace_floor_div<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_InPlaceFloorDivide)
+    }
+
+    /// This is equivalent to the Python statement 'self %= other'.
+    #[inline]
+    fn inplace_rem<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_InPlaceRemainder)
+    }
+
+    /// This is equivalent to the Python statement 'self **= other'.
+    #[inline]
+    fn inplace_pow<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, number_inplace_power)
+    }
+
+    /// This is equivalent to the Python statement 'self <<= other'.
+    #[inline]
+    fn inplace_lshift<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_InPlaceLshift)
+    }
+
+    /// This is equivalent to the Python statement 'self >>= other'.
+    #[inline]
+    fn inplace_rshift<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_InPlaceRshift)
+    }
+
+    /// This is equivalent to the Python statement 'self &= other'.
+    #[inline]
+    fn inplace_bitand<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_InPlaceAnd)
+    }
+
+    /// This is equivalent to the Python statement 'self |= other'.
+    #[inline]
+    fn inplace_bitor<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_InPlaceOr)
+    }
+
+    /// This is equivalent to the Python statement 'self ^= other'.
+    #[inline]
+    fn inplace_bitxor<O>(&self, py: Python, other: O) -> PyResult<PyObject> where O: ToPyObject {
+        number_binary_op(py, self.as_object(), other, ffi::PyNumber_InPlaceXor)
+    }
 }
 
 impl ObjectProtocol for PyObject {}
 
+type BinaryNumberFn = unsafe extern "C" fn(*mut ffi::PyObject, *mut ffi::PyObject) -> *mut ffi::PyObject;
+type UnaryNumberFn = unsafe extern "C" fn(*mut ffi::PyObject) -> *mut ffi::PyObject;
+
+fn number_binary_op<O>(py: Python, obj: &PyObject, other: O, op: BinaryNumberFn) -> PyResult<PyObject> where O: ToPyObject {
+    other.with_borrowed_ptr(py, |other| unsafe {
+        err::result_from_owned_ptr(py, op(obj.as_ptr(), other))
+    })
+}
+
+fn number_unary_op(py: Python, obj: &PyObject, op: UnaryNumberFn) -> PyResult<PyObject> {
+    unsafe { err::result_from_owned_ptr(py, op(obj.as_ptr())) }
+}
+
+// Two-argument forms of `pow()`, so they fit `number_binary_op`.
+unsafe extern "C" fn number_power(a: *mut ffi::PyObject, b: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    ffi::PyNumber_Power(a, b, ffi::Py_None())
+}
+
+unsafe extern "C" fn number_inplace_power(a: *mut ffi::PyObject, b: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    ffi::PyNumber_InPlacePower(a, b, ffi::Py_None())
+}
+
+fn rich_compare_bool<O>(py: Python, obj: &PyObject, other: O, op: CompareOp) -> PyResult<bool> where O: ToPyObject {
+    other.with_borrowed_ptr(py, |other| {
+        let result = unsafe { ffi::PyObject_RichCompareBool(obj.as_ptr(), other, op as libc::c_int) };
+        if result == -1 {
+            Err(PyErr::fetch(py))
+        } else {
+            Ok(result != 0)
+        }
+    })
+}
+
 impl fmt::Debug for PyObject {
     fn fmt(&self, f : &mut fmt::Formatter) -> Result<(), fmt::Error> {
         // TODO: we shouldn't use fmt::Error when repr() fails
@@ -246,9 +556,13 @@ impl fmt::Display for PyObject {
 #[cfg(test)]
 mod test {
     use std;
-    use python::{Python, PythonObject};
+    use python::{Python, PythonObject, PyClone};
     use conversion::ToPyObject;
-    use objects::{PyList, PyTuple};
+    use objects::{PyList, PyTuple, PyDict, PyString, PyType};
+    use py_class::CompareOp;
+    use err::PyResult;
+    use objects::PyObject;
+    use super::ObjectProtocol;
 
     #[test]
     fn test_debug_string() {
@@ -265,5 +579,109 @@ mod test {
         let v = "Hello\n".to_py_object(py).into_object();
         assert_eq!(format!("{}", v), "Hello\n");
     }
+
+    #[test]
+    fn test_rich_compare() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let one = 1i32.to_py_object(py).into_object();
+        assert!(one.rich_lt(py, 2).unwrap());
+        assert!(one.rich_le(py, 1).unwrap());
+        assert!(!one.rich_gt(py, 1).unwrap());
+        assert!(one.rich_ge(py, 1.0).unwrap());
+        assert!(one.rich_eq(py, 1.0).unwrap());
+        assert!(one.rich_ne(py, "1").unwrap());
+        let result = one.rich_compare(py, 0, CompareOp::Gt).unwrap();
+        assert!(result.extract::<bool>(py).unwrap());
+        // Python 3 does not define an order between numbers and strings.
+        #[cfg(feature="python3-sys")]
+        assert!(one.rich_lt(py, "a").is_err());
+    }
+
+    #[test]
+    fn test_rich_compare_eq_only() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let cls = py.eval("type('C', (object,), {'__eq__': lambda self, other: other == 42})", None, None).unwrap();
+        let obj = cls.call(py, ::objects::NoArgs, None).unwrap();
+        assert!(obj.rich_eq(py, 42).unwrap());
+        assert!(!obj.rich_eq(py, 41).unwrap());
+    }
+
+    #[test]
+    fn test_number_protocol() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let seven = 7i32.to_py_object(py).into_object();
+        let value = |obj: PyResult<PyObject>| obj.unwrap().extract::<f64>(py).unwrap();
+        assert_eq!(value(seven.add(py, 2)), 9.0);
+        assert_eq!(value(seven.sub(py, 2)), 5.0);
+        assert_eq!(value(seven.mul(py, 2)), 14.0);
+        assert_eq!(value(seven.true_div(py, 2)), 3.5);
+        assert_eq!(value(seven.floor_div(py, 2)), 3.0);
+        assert_eq!(value(seven.rem(py, 2)), 1.0);
+        assert_eq!(value(seven.pow(py, 2)), 49.0);
+        assert_eq!(value(seven.lshift(py, 2)), 28.0);
+        assert_eq!(value(seven.rshift(py, 1)), 3.0);
+        assert_eq!(value(seven.bitand(py, 3)), 3.0);
+        assert_eq!(value(seven.bitor(py, 8)), 15.0);
+        assert_eq!(value(seven.bitxor(py, 1)), 6.0);
+        assert_eq!(value(seven.neg(py)), -7.0);
+        assert_eq!(value(seven.neg(py).unwrap().abs(py)), 7.0);
+        assert_eq!(value(seven.pos(py)), 7.0);
+        assert_eq!(value(seven.invert(py)), -8.0);
+        assert_eq!(seven.divmod(py, 2).unwrap().extract::<(i32, i32)>(py).unwrap(), (3, 1));
+        assert!(seven.true_div(py, 0).is_err());
+        assert!(seven.add(py, "a").is_err());
+    }
+
+    #[test]
+    fn test_number_protocol_reflected() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let cls = py.eval("type('C', (object,), {'__radd__': lambda self, other: other * 10})", None, None).unwrap();
+        let obj = cls.call(py, ::objects::NoArgs, None).unwrap();
+        let one = 1i32.to_py_object(py).into_object();
+        assert_eq!(one.add(py, obj).unwrap().extract::<i32>(py).unwrap(), 10);
+    }
+
+    #[test]
+    fn test_number_protocol_inplace() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = PyList::new(py, &[1i32.to_py_object(py).into_object()]).into_object();
+        let result = list.inplace_add(py, PyList::new(py, &[2i32.to_py_object(py).into_object()])).unwrap();
+        assert_eq!(result, list);
+        assert_eq!(list.extract::<Vec<i32>>(py).unwrap(), vec![1, 2]);
+
+        let one = 1i32.to_py_object(py).into_object();
+        assert_eq!(one.inplace_add(py, 1).unwrap().extract::<i32>(py).unwrap(), 2);
+        assert_eq!(one.inplace_mul(py, 5).unwrap().extract::<i32>(py).unwrap(), 5);
+        assert_eq!(one.extract::<i32>(py).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_is_instance() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py).into_object();
+        assert!(dict.is_instance(py, &py.get_type::<PyDict>()).unwrap());
+        assert!(!dict.is_instance(py, &py.get_type::<PyList>()).unwrap());
+        let bool_type = py.True().into_object().get_type(py);
+        let int_type = py.eval("int", None, None).unwrap().cast_into::<PyType>(py).unwrap();
+        assert!(bool_type.as_object().is_subclass(py, &int_type).unwrap());
+        assert!(!py.get_type::<PyDict>().as_object().is_subclass(py, &py.get_type::<PyList>()).unwrap());
+    }
+
+    #[test]
+    fn test_dir_and_id() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = PyString::new(py, "abc").into_object();
+        let names: Vec<String> = obj.dir(py).unwrap().into_object().extract(py).unwrap();
+        assert!(names.iter().any(|name| name == "upper"));
+        let id: usize = py.eval("id", None, None).unwrap().call(py, (obj.clone_ref(py),), None).unwrap().extract(py).unwrap();
+        assert_eq!(obj.id(py), id);
+    }
 }
 
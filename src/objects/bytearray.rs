@@ -0,0 +1,163 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::slice;
+use libc::c_char;
+use ffi;
+use python::{Python, PythonObject};
+use err::{self, PyResult};
+use super::object::PyObject;
+
+/// Represents a Python `bytearray`: a mutable sequence of bytes.
+pub struct PyByteArray(PyObject);
+
+pyobject_newtype!(PyByteArray, PyByteArray_Check, PyByteArray_Type);
+
+impl PyByteArray {
+    /// Creates a new Python `bytearray` object.
+    /// The byte array is initialized by copying the data from the `&[u8]`.
+    ///
+    /// Panics if out of memory.
+    pub fn new(py: Python, src: &[u8]) -> PyByteArray {
+        let ptr = src.as_ptr() as *const c_char;
+        let len = src.len() as ffi::Py_ssize_t;
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(py,
+                ffi::PyByteArray_FromStringAndSize(ptr, len))
+        }
+    }
+
+    /// Creates a new Python `bytearray` object from any object that
+    /// supports the buffer protocol or is an iterable of integers.
+    /// This is equivalent to the python expression `bytearray(src)`.
+    pub fn from_object(py: Python, src: &PyObject) -> PyResult<PyByteArray> {
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyByteArray_FromObject(src.as_ptr()))
+        }
+    }
+
+    /// Gets the length of the byte array.
+    #[inline]
+    pub fn len(&self, _py: Python) -> usize {
+        unsafe { ffi::PyByteArray_Size(self.0.as_ptr()) as usize }
+    }
+
+    /// Resizes the byte array to `len` bytes.
+    /// New bytes are not initialized.
+    ///
+    /// Fails with `BufferError` while the byte array is exported via the buffer protocol.
+    pub fn resize(&self, py: Python, len: usize) -> PyResult<()> {
+        unsafe {
+            err::error_on_minusone(py,
+                ffi::PyByteArray_Resize(self.0.as_ptr(), len as ffi::Py_ssize_t))
+        }
+    }
+
+    /// Copies the contents of the byte array into a new `Vec<u8>`.
+    pub fn to_vec(&self, py: Python) -> Vec<u8> {
+        unsafe { self.as_slice(py) }.to_vec()
+    }
+
+    /// Gets the contents of the byte array as a slice.
+    ///
+    /// # Safety
+    /// The slice points directly into the byte array's storage.
+    /// It is only valid while the GIL is held, and only as long as the
+    /// byte array is not resized or otherwise modified -- which can happen
+    /// whenever Python code runs, or when `resize()` or `as_mut_slice()`
+    /// are called.
+    pub unsafe fn as_slice(&self, _py: Python) -> &[u8] {
+        let buffer = ffi::PyByteArray_AsString(self.0.as_ptr()) as *const u8;
+        let length = ffi::PyByteArray_Size(self.0.as_ptr()) as usize;
+        if length == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(buffer, length)
+        }
+    }
+
+    /// Gets the contents of the byte array as a mutable slice.
+    ///
+    /// This allows filling a byte array in place, e.g. from a `Read` implementation.
+    ///
+    /// # Safety
+    /// The same restrictions as for `as_slice()` apply.
+    /// Additionally, no other slice into the byte array may be alive
+    /// while the mutable slice is used.
+    pub unsafe fn as_mut_slice(&mut self, _py: Python) -> &mut [u8] {
+        let buffer = ffi::PyByteArray_AsString(self.0.as_ptr()) as *mut u8;
+        let length = ffi::PyByteArray_Size(self.0.as_ptr()) as usize;
+        if length == 0 {
+            &mut []
+        } else {
+            slice::from_raw_parts_mut(buffer, length)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objects::{PyByteArray, PyObject};
+    use objectprotocol::ObjectProtocol;
+
+    #[test]
+    fn test_new() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let array = PyByteArray::new(py, b"abc");
+        assert_eq!(3, array.len(py));
+        assert_eq!(b"abc".to_vec(), array.to_vec(py));
+        let obj = array.to_py_object(py).into_object();
+        assert_eq!("bytearray(b'abc')", obj.repr(py).unwrap().to_string_lossy(py));
+    }
+
+    #[test]
+    fn test_extract() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj: PyObject = py.eval("bytearray(b'xyz')", None, None).unwrap();
+        let array = obj.extract::<PyByteArray>(py).unwrap();
+        assert_eq!(b"xyz", unsafe { array.as_slice(py) });
+        assert!(py.eval("b'xyz'", None, None).unwrap().extract::<PyByteArray>(py).is_err());
+    }
+
+    #[test]
+    fn test_from_object() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("[1, 2, 3]", None, None).unwrap();
+        let array = PyByteArray::from_object(py, &obj).unwrap();
+        assert_eq!(vec![1, 2, 3], array.to_vec(py));
+        assert!(PyByteArray::from_object(py, &py.None()).is_err());
+    }
+
+    #[test]
+    fn test_resize_and_write() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut array = PyByteArray::new(py, b"");
+        array.resize(py, 4).unwrap();
+        assert_eq!(4, array.len(py));
+        unsafe { array.as_mut_slice(py) }.copy_from_slice(b"data");
+        assert_eq!(b"data".to_vec(), array.to_vec(py));
+        array.resize(py, 0).unwrap();
+        assert_eq!(b"", unsafe { array.as_slice(py) });
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::ffi::{CStr, CString};
+use std::mem;
+use libc::c_void;
+use ffi;
+use python::{Python, PythonObject};
+use err::{self, PyResult, PyErr};
+use function::AbortOnDrop;
+use super::object::PyObject;
+use super::exc;
+
+/// Represents a Python capsule: an opaque pointer to native data with a name,
+/// which is usually stored as a module attribute so that other extension modules
+/// can access a native API -- like the C-API of the `datetime` module.
+///
+/// # Example
+/// ```
+/// use cpython::{Python, PyCapsule, PyModule, ObjectProtocol};
+///
+/// pub struct Api {
+///     pub add: fn(i32, i32) -> i32
+/// }
+///
+/// fn add(a: i32, b: i32) -> i32 { a + b }
+///
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+///
+/// // in the extension module providing the API:
+/// let module = PyModule::new(py, "provider").unwrap();
+/// let capsule = PyCapsule::new(py, Api { add: add }, "provider._api").unwrap();
+/// module.add(py, "_api", capsule).unwrap();
+/// # let sys = py.import("sys").unwrap();
+/// # sys.get(py, "modules").unwrap().set_item(py, "provider", module).unwrap();
+///
+/// // in an extension module using the API:
+/// let api = unsafe { PyCapsule::import::<Api>(py, "provider._api") }.unwrap();
+/// assert_eq!(5, (api.add)(2, 3));
+/// ```
+pub struct PyCapsule(PyObject);
+
+pyobject_newtype!(PyCapsule, PyCapsule_CheckExact, PyCapsule_Type);
+
+impl PyCapsule {
+    /// Creates a new capsule that owns `value`.
+    ///
+    /// The value is dropped when the capsule is destroyed.
+    /// By convention, the name is the full path under which the capsule is
+    /// stored (`"module.attribute"`), so that `PyCapsule::import` can find it.
+    ///
+    /// Raises `ValueError` if the name contains a NUL byte.
+    pub fn new<T>(py: Python, value: T, name: &str) -> PyResult<PyCapsule>
+        where T: Send + 'static
+    {
+        let name = try!(capsule_name(py, name));
+        let pointer = Box::into_raw(Box::new(value)) as *mut c_void;
+        unsafe {
+            let capsule = ffi::PyCapsule_New(pointer, name.as_ptr(), Some(capsule_destructor::<T>));
+            if capsule.is_null() {
+                drop(Box::from_raw(pointer as *mut T));
+                return Err(PyErr::fetch(py));
+            }
+            // The capsule only stores the pointer to its name,
+            // so keep the name alive as the capsule's context.
+            ffi::PyCapsule_SetContext(capsule, name.into_raw() as *mut c_void);
+            Ok(err::cast_from_owned_ptr_or_panic(py, capsule))
+        }
+    }
+
+    /// Gets the name of the capsule, if it has one.
+    pub fn name(&self, _py: Python) -> Option<&CStr> {
+        unsafe {
+            let name = ffi::PyCapsule_GetName(self.0.as_ptr());
+            if name.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(name))
+            }
+        }
+    }
+
+    /// Gets the raw pointer stored in the capsule.
+    pub fn pointer(&self, _py: Python) -> *mut c_void {
+        unsafe {
+            ffi::PyCapsule_GetPointer(self.0.as_ptr(), ffi::PyCapsule_GetName(self.0.as_ptr()))
+        }
+    }
+
+    /// Gets a reference to the value stored in the capsule.
+    ///
+    /// # Safety
+    /// The capsule must contain a value of type `T`:
+    /// either it was created by `PyCapsule::new::<T>`, or its pointer points to a `T`.
+    pub unsafe fn data_ref<T>(&self, py: Python) -> &T {
+        &*(self.pointer(py) as *const T)
+    }
+
+    /// Imports the capsule stored at `name` (`"module.attribute"` or
+    /// `"package.module.attribute"`) and returns a reference to its value.
+    ///
+    /// Fails if the module cannot be imported, if the attribute is not a capsule,
+    /// or if the capsule's name is not exactly `name`.
+    /// Raises `ValueError` if `name` contains a NUL byte.
+    ///
+    /// # Safety
+    /// The capsule must contain a value of type `T`.
+    /// The returned reference is only valid as long as the capsule is kept alive
+    /// by the module attribute; this is usually the case for the whole lifetime
+    /// of the interpreter.
+    pub unsafe fn import<'p, T>(py: Python<'p>, name: &str) -> PyResult<&'p T> {
+        let name = try!(capsule_name(py, name));
+        let pointer = ffi::PyCapsule_Import(name.as_ptr(), 0);
+        if pointer.is_null() {
+            Err(PyErr::fetch(py))
+        } else {
+            Ok(&*(pointer as *const T))
+        }
+    }
+}
+
+fn capsule_name(py: Python, name: &str) -> PyResult<CString> {
+    CString::new(name).map_err(|_| PyErr::new::<exc::ValueError, _>(py, "embedded null character"))
+}
+
+unsafe extern "C" fn capsule_destructor<T>(capsule: *mut ffi::PyObject) {
+    let guard = AbortOnDrop("capsule destructor");
+    let pointer = ffi::PyCapsule_GetPointer(capsule, ffi::PyCapsule_GetName(capsule));
+    let name = ffi::PyCapsule_GetContext(capsule);
+    drop(Box::from_raw(pointer as *mut T));
+    drop(CString::from_raw(name as *mut _));
+    mem::forget(guard);
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use python::{Python, PythonObject};
+    use objects::{PyCapsule, PyModule, PyObject, exc};
+    use objectprotocol::ObjectProtocol;
+
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let capsule = PyCapsule::new(py, (1u32, String::from("abc")), "test.capsule").unwrap();
+        assert_eq!("test.capsule", capsule.name(py).unwrap().to_str().unwrap());
+        let data = unsafe { capsule.data_ref::<(u32, String)>(py) };
+        assert_eq!((1, String::from("abc")), *data);
+        let obj: PyObject = capsule.into_object();
+        assert!(obj.cast_as::<PyCapsule>(py).is_ok());
+    }
+
+    #[test]
+    fn test_drop() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let capsule = PyCapsule::new(py, DropFlag(dropped.clone()), "test.drop").unwrap();
+        assert!(!dropped.load(Ordering::Relaxed));
+        drop(capsule);
+        assert!(dropped.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_new_nul_in_name() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let err = PyCapsule::new(py, DropFlag(dropped.clone()), "test\0drop").err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::ValueError>()));
+        assert!(dropped.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_import() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let module = PyModule::new(py, "capsule_test_module").unwrap();
+        module.add(py, "api", PyCapsule::new(py, 42i64, "capsule_test_module.api").unwrap()).unwrap();
+        module.add(py, "misnamed", PyCapsule::new(py, 42i64, "other.name").unwrap()).unwrap();
+        let modules = py.import("sys").unwrap().get(py, "modules").unwrap();
+        modules.set_item(py, "capsule_test_module", &module).unwrap();
+
+        assert_eq!(42, *unsafe { PyCapsule::import::<i64>(py, "capsule_test_module.api") }.unwrap());
+        assert!(unsafe { PyCapsule::import::<i64>(py, "capsule_test_module.misnamed") }.is_err());
+        assert!(unsafe { PyCapsule::import::<i64>(py, "capsule_test_module.missing") }.is_err());
+        let err = unsafe { PyCapsule::import::<i64>(py, "capsule_test_module\0api") }.err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::ValueError>()));
+        modules.del_item(py, "capsule_test_module").unwrap();
+    }
+}
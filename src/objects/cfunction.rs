@@ -0,0 +1,197 @@
+// Copyright (c) 2017 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::ffi::CString;
+use std::{mem, ptr};
+use ffi;
+use python::{Python, PythonObject, PyDrop};
+use conversion::ToPyObject;
+use err::{self, PyResult};
+use function::{handle_callback, PyObjectCallbackConverter};
+use super::object::PyObject;
+use super::{PyCapsule, PyDict, PyTuple};
+
+/// Represents a Python built-in function object, like the functions created by `py_fn!`.
+pub struct PyCFunction(PyObject);
+
+pyobject_newtype!(PyCFunction, PyCFunction_Check, PyCFunction_Type);
+
+/// Name of the capsules that hold the closures of functions created by `PyCFunction::from_closure`.
+const CLOSURE_CAPSULE_NAME: &'static str = "cpython.PyCFunction.closure";
+
+/// The contents of the capsule used as `self` of a closure function.
+struct ClosureData<F> {
+    def: ffi::PyMethodDef,
+    // keeps `def.ml_name` alive
+    _name: CString,
+    closure: F
+}
+
+// The method definition only contains pointers to the name and to a function.
+unsafe impl<F: Send> Send for ClosureData<F> {}
+
+impl PyCFunction {
+    /// Creates a Python callable object that invokes the Rust closure `f`.
+    ///
+    /// Unlike `py_fn!`, this allows the function to capture state, such as a
+    /// connection handle or a channel sender.
+    /// The closure receives the positional arguments as tuple, and the keyword arguments
+    /// as dict (if any were passed); use `py_argparse!` to parse them.
+    /// The closure is dropped when the Python function object is destroyed.
+    ///
+    /// If the closure panics, the Python function raises `SystemError`.
+    ///
+    /// Panics if the name contains a NUL byte, or if out of memory.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::mpsc;
+    /// use cpython::{Python, PythonObject, PyCFunction, ObjectProtocol};
+    ///
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// let (sender, receiver) = mpsc::channel();
+    /// let sender = std::sync::Mutex::new(sender);
+    /// let send = PyCFunction::from_closure(py, "send", move |py, args, _kwargs| {
+    ///     let value: i32 = args.get_item(py, 0).extract(py)?;
+    ///     sender.lock().unwrap().send(value).unwrap();
+    ///     Ok(py.None())
+    /// });
+    /// send.as_object().call(py, (42,), None).unwrap();
+    /// assert_eq!(receiver.recv().unwrap(), 42);
+    /// ```
+    pub fn from_closure<F, R>(py: Python, name: &str, f: F) -> PyCFunction
+        where F: Fn(Python, &PyTuple, Option<&PyDict>) -> PyResult<R> + Send + 'static,
+              R: ToPyObject
+    {
+        let name = CString::new(name).unwrap();
+        let data = ClosureData {
+            def: ffi::PyMethodDef {
+                ml_name: name.as_ptr(),
+                ml_meth: Some(unsafe {
+                    mem::transmute::<ffi::PyCFunctionWithKeywords, ffi::PyCFunction>(call_closure::<F, R>)
+                }),
+                ml_flags: ffi::METH_VARARGS | ffi::METH_KEYWORDS,
+                ml_doc: ptr::null()
+            },
+            _name: name,
+            closure: f
+        };
+        let capsule = PyCapsule::new(py, data, CLOSURE_CAPSULE_NAME)
+            .unwrap_or_else(|e| panic!("Failed to create closure capsule: {:?}", e));
+        unsafe {
+            // The method definition lives in the capsule, which the function keeps alive as `self`.
+            let data = capsule.pointer(py) as *mut ClosureData<F>;
+            let result = err::cast_from_owned_ptr_or_panic(py,
+                ffi::PyCFunction_New(&mut (*data).def, capsule.as_object().as_ptr()));
+            capsule.release_ref(py);
+            result
+        }
+    }
+}
+
+unsafe extern "C" fn call_closure<F, R>(
+    slf: *mut ffi::PyObject,
+    args: *mut ffi::PyObject,
+    kwargs: *mut ffi::PyObject) -> *mut ffi::PyObject
+    where F: Fn(Python, &PyTuple, Option<&PyDict>) -> PyResult<R> + Send + 'static,
+          R: ToPyObject
+{
+    handle_callback("PyCFunction closure", PyObjectCallbackConverter, |py| {
+        let data = ffi::PyCapsule_GetPointer(slf, ffi::PyCapsule_GetName(slf)) as *const ClosureData<F>;
+        let args: PyTuple = PyObject::from_borrowed_ptr(py, args).unchecked_cast_into();
+        let kwargs: Option<PyDict> = PyObject::from_borrowed_ptr_opt(py, kwargs)
+            .map(|kwargs| kwargs.unchecked_cast_into());
+        let ret = ((*data).closure)(py, &args, kwargs.as_ref());
+        args.release_ref(py);
+        kwargs.release_ref(py);
+        ret
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use python::{Python, PythonObject};
+    use objectprotocol::ObjectProtocol;
+    use objects::{exc, PyCFunction, PyDict, PyObject};
+    use err::PyErr;
+
+    #[test]
+    fn test_closure() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter2 = counter.clone();
+        let f = PyCFunction::from_closure(py, "count", move |py, args, kwargs| {
+            let step: usize = match kwargs.and_then(|kwargs| kwargs.get_item(py, "step")) {
+                Some(step) => step.extract(py)?,
+                None => 1
+            };
+            assert_eq!(args.len(py), 0);
+            Ok(counter2.fetch_add(step, Ordering::Relaxed) + step)
+        }).into_object();
+        assert_eq!(f.call(py, ::objects::NoArgs, None).unwrap().extract::<usize>(py).unwrap(), 1);
+        let kwargs = PyDict::new(py);
+        kwargs.set_item(py, "step", 5).unwrap();
+        assert_eq!(f.call(py, ::objects::NoArgs, Some(&kwargs)).unwrap().extract::<usize>(py).unwrap(), 6);
+        assert_eq!(counter.load(Ordering::Relaxed), 6);
+        assert_eq!(f.getattr(py, "__name__").unwrap().extract::<String>(py).unwrap(), "count");
+        assert!(f.cast_as::<PyCFunction>(py).is_ok());
+    }
+
+    #[test]
+    fn test_closure_error_and_panic() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let f = PyCFunction::from_closure(py, "fail", |py, args, _| -> Result<PyObject, PyErr> {
+            if args.len(py) == 0 {
+                panic!("no arguments");
+            }
+            Err(PyErr::new::<exc::KeyError, _>(py, "key"))
+        }).into_object();
+        let err = f.call(py, (1,), None).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::KeyError>()));
+        let err = f.call(py, ::objects::NoArgs, None).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::SystemError>()));
+    }
+
+    #[test]
+    fn test_closure_dropped() {
+        struct DropFlag(Arc<AtomicBool>);
+
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::Relaxed);
+            }
+        }
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let flag = DropFlag(dropped.clone());
+        let f = PyCFunction::from_closure(py, "f", move |py, _, _| {
+            let _ = &flag;
+            Ok(py.None())
+        });
+        assert!(!dropped.load(Ordering::Relaxed));
+        drop(f);
+        assert!(dropped.load(Ordering::Relaxed));
+    }
+}
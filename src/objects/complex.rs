@@ -0,0 +1,153 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+#[cfg(feature="num-complex")]
+extern crate num_complex;
+
+use libc::c_double;
+use python::{Python, PythonObject};
+use err;
+use super::object::PyObject;
+use ffi;
+
+/// Represents a Python `complex` object.
+///
+/// When the `num-complex` feature is enabled, you can usually avoid
+/// directly working with this type by using [ToPyObject](trait.ToPyObject.html)
+/// and [extract](struct.PyObject.html#method.extract)
+/// with `num_complex::Complex<f64>`/`Complex<f32>`.
+pub struct PyComplex(PyObject);
+pyobject_newtype!(PyComplex, PyComplex_Check, PyComplex_Type);
+
+impl PyComplex {
+    /// Creates a new Python `complex` object.
+    pub fn new(py: Python, real: c_double, imag: c_double) -> PyComplex {
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(py, ffi::PyComplex_FromDoubles(real, imag))
+        }
+    }
+
+    /// Gets the real part of this complex number.
+    pub fn real(&self, _py: Python) -> c_double {
+        unsafe { ffi::PyComplex_RealAsDouble(self.0.as_ptr()) }
+    }
+
+    /// Gets the imaginary part of this complex number.
+    pub fn imag(&self, _py: Python) -> c_double {
+        unsafe { ffi::PyComplex_ImagAsDouble(self.0.as_ptr()) }
+    }
+}
+
+#[cfg(feature="num-complex")]
+mod num_complex_conversions {
+    use super::num_complex::Complex;
+    use libc::c_double;
+    use ffi;
+    use python::{Python, PythonObject};
+    use err::{PyResult, PyErr};
+    use objects::PyObject;
+    use conversion::ToPyObject;
+    use super::PyComplex;
+
+    /// Converts `obj` to a pair `(real, imag)`.
+    ///
+    /// Like Python's `complex(obj)`, this honors `__complex__`, and falls back to
+    /// `__float__` for real numbers; but unlike `complex()` it does not parse strings.
+    fn extract_complex(py: Python, obj: &PyObject) -> PyResult<(c_double, c_double)> {
+        let v = unsafe { ffi::PyComplex_AsCComplex(obj.as_ptr()) };
+        if v.real == -1.0 && PyErr::occurred(py) {
+            Err(PyErr::fetch(py))
+        } else {
+            Ok((v.real, v.imag))
+        }
+    }
+
+    impl ToPyObject for Complex<f64> {
+        type ObjectType = PyComplex;
+
+        fn to_py_object(&self, py: Python) -> PyComplex {
+            PyComplex::new(py, self.re, self.im)
+        }
+    }
+
+    impl ToPyObject for Complex<f32> {
+        type ObjectType = PyComplex;
+
+        fn to_py_object(&self, py: Python) -> PyComplex {
+            PyComplex::new(py, self.re as f64, self.im as f64)
+        }
+    }
+
+    extract!(obj to Complex<f64>; py => {
+        let (re, im) = try!(extract_complex(py, obj));
+        Ok(Complex::new(re, im))
+    });
+
+    extract!(obj to Complex<f32>; py => {
+        let (re, im) = try!(extract_complex(py, obj));
+        Ok(Complex::new(re as f32, im as f32))
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use objects::PyComplex;
+
+    #[test]
+    fn test_new() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let c = PyComplex::new(py, 1.5, -2.0);
+        assert_eq!(1.5, c.real(py));
+        assert_eq!(-2.0, c.imag(py));
+        assert_eq!("(1.5-2j)", c.into_object().to_string());
+    }
+
+    #[test]
+    fn test_cast() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let c = py.eval("3+4j", None, None).unwrap();
+        let c = c.cast_as::<PyComplex>(py).unwrap();
+        assert_eq!(3.0, c.real(py));
+        assert_eq!(4.0, c.imag(py));
+        assert!(py.eval("3.0", None, None).unwrap().cast_as::<PyComplex>(py).is_err());
+    }
+
+    #[test]
+    #[cfg(feature="num-complex")]
+    fn test_num_complex() {
+        use objects::complex::num_complex::Complex;
+        use conversion::ToPyObject;
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let c = Complex::new(0.5f64, 2.0);
+        let obj = c.to_py_object(py).into_object();
+        assert_eq!(c, obj.extract::<Complex<f64>>(py).unwrap());
+        assert_eq!(Complex::new(0.5f32, 2.0), obj.extract::<Complex<f32>>(py).unwrap());
+
+        // real numbers and objects implementing `__complex__` are accepted
+        let obj = py.eval("2", None, None).unwrap();
+        assert_eq!(Complex::new(2.0, 0.0), obj.extract::<Complex<f64>>(py).unwrap());
+        let obj = py.eval("type('C', (object,), {'__complex__': lambda self: 1-1j})()", None, None).unwrap();
+        assert_eq!(Complex::new(1.0, -1.0), obj.extract::<Complex<f64>>(py).unwrap());
+        assert!(py.eval("'1+1j'", None, None).unwrap().extract::<Complex<f64>>(py).is_err());
+    }
+}
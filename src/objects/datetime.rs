@@ -0,0 +1,777 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+#[cfg(feature="chrono")]
+extern crate chrono;
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use libc::c_int;
+use ffi;
+use python::{Python, PythonObject};
+use conversion::{ToPyObject, FromPyObject};
+use err::{self, PyErr, PyResult};
+use super::object::PyObject;
+use super::exc;
+
+/// Gets the `datetime` C-API, importing the `datetime` module on first use.
+///
+/// Returns the `ImportError` if the `datetime` module cannot be imported.
+fn datetime_api(py: Python) -> PyResult<&'static ffi::PyDateTime_CAPI> {
+    unsafe {
+        // The GIL protects the initialization of `PyDateTimeAPI`.
+        if ffi::PyDateTimeAPI.is_null() {
+            ffi::PyDateTime_IMPORT();
+            if ffi::PyDateTimeAPI.is_null() {
+                return Err(PyErr::fetch(py));
+            }
+        }
+        Ok(&*ffi::PyDateTimeAPI)
+    }
+}
+
+// The type objects of the datetime types are only available through the C-API capsule,
+// so we can't use the three-argument form of pyobject_newtype!.
+macro_rules! datetime_newtype(
+    ($name: ident, $checkfunction: ident, $typefield: ident) => (
+        pyobject_newtype!($name);
+
+        impl ::python::PythonObjectWithCheckedDowncast for $name {
+            #[inline]
+            fn downcast_from<'p>(py: Python<'p>, obj: PyObject) -> Result<$name, ::python::PythonObjectDowncastError<'p>> {
+                // If the datetime module can't be imported, `obj` can't be one of its types.
+                if datetime_api(py).is_err() {
+                    return Err(::python::PythonObjectDowncastError(py));
+                }
+                unsafe {
+                    if ffi::$checkfunction(obj.as_ptr()) != 0 {
+                        Ok($name(obj))
+                    } else {
+                        Err(::python::PythonObjectDowncastError(py))
+                    }
+                }
+            }
+
+            #[inline]
+            fn downcast_borrow_from<'a, 'p>(py: Python<'p>, obj: &'a PyObject) -> Result<&'a $name, ::python::PythonObjectDowncastError<'p>> {
+                // If the datetime module can't be imported, `obj` can't be one of its types.
+                if datetime_api(py).is_err() {
+                    return Err(::python::PythonObjectDowncastError(py));
+                }
+                unsafe {
+                    if ffi::$checkfunction(obj.as_ptr()) != 0 {
+                        Ok(::std::mem::transmute(obj))
+                    } else {
+                        Err(::python::PythonObjectDowncastError(py))
+                    }
+                }
+            }
+        }
+
+        /// Panics if the `datetime` module cannot be imported.
+        impl ::python::PythonObjectWithTypeObject for $name {
+            #[inline]
+            fn type_object(py: Python) -> ::objects::typeobject::PyType {
+                let api = datetime_api(py)
+                    .unwrap_or_else(|e| panic!("Failed to import the datetime C-API: {:?}", e));
+                unsafe { ::objects::typeobject::PyType::from_type_ptr(py, api.$typefield) }
+            }
+        }
+    )
+);
+
+/// Gets the `tzinfo` stored in a `time` or `datetime` object.
+unsafe fn get_tzinfo(py: Python, hastzinfo: bool, tzinfo: *mut ffi::PyObject) -> Option<PyTzInfo> {
+    if hastzinfo && tzinfo != ffi::Py_None() {
+        Some(PyObject::from_borrowed_ptr(py, tzinfo).unchecked_cast_into::<PyTzInfo>())
+    } else {
+        None
+    }
+}
+
+/// Gets a borrowed pointer to the `tzinfo` argument for the C-API constructors.
+fn tzinfo_ptr(tzinfo: Option<&PyTzInfo>) -> *mut ffi::PyObject {
+    match tzinfo {
+        Some(tzinfo) => tzinfo.as_object().as_ptr(),
+        None => unsafe { ffi::Py_None() }
+    }
+}
+
+/// Validates the arguments of the date constructors.
+///
+/// Python 2.7 does not validate the arguments of the C-API constructors,
+/// so we check them here with the same messages as the `date` constructor.
+fn check_date(py: Python, year: i32, month: u8, day: u8) -> PyResult<()> {
+    if year < 1 || year > 9999 {
+        return Err(PyErr::new::<exc::ValueError, _>(py, format!("year {} is out of range", year)));
+    }
+    if month < 1 || month > 12 {
+        return Err(PyErr::new::<exc::ValueError, _>(py, "month must be in 1..12"));
+    }
+    let is_leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if is_leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
+    };
+    if day < 1 || day > days_in_month {
+        return Err(PyErr::new::<exc::ValueError, _>(py, "day is out of range for month"));
+    }
+    Ok(())
+}
+
+/// Validates the arguments of the time constructors.
+fn check_time(py: Python, hour: u8, minute: u8, second: u8, microsecond: u32) -> PyResult<()> {
+    let message = if hour > 23 {
+        "hour must be in 0..23"
+    } else if minute > 59 {
+        "minute must be in 0..59"
+    } else if second > 59 {
+        "second must be in 0..59"
+    } else if microsecond > 999_999 {
+        "microsecond must be in 0..999999"
+    } else {
+        return Ok(());
+    };
+    Err(PyErr::new::<exc::ValueError, _>(py, message))
+}
+
+/// Represents a Python `datetime.date` object.
+pub struct PyDate(PyObject);
+datetime_newtype!(PyDate, PyDate_Check, DateType);
+
+impl PyDate {
+    /// Creates a new `datetime.date` object.
+    ///
+    /// Raises `ValueError` if the date is invalid.
+    pub fn new(py: Python, year: i32, month: u8, day: u8) -> PyResult<PyDate> {
+        try!(check_date(py, year, month, day));
+        let api = try!(datetime_api(py));
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                (api.Date_FromDate)(year as c_int, month as c_int, day as c_int, api.DateType))
+        }
+    }
+
+    /// Creates a `datetime.date` from a POSIX timestamp,
+    /// like `date.fromtimestamp(timestamp)`.
+    pub fn from_timestamp(py: Python, timestamp: f64) -> PyResult<PyDate> {
+        let args = (timestamp,).to_py_object(py);
+        try!(datetime_api(py));
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyDate_FromTimestamp(args.as_object().as_ptr()))
+        }
+    }
+
+    /// Gets the year.
+    pub fn year(&self, _py: Python) -> i32 {
+        unsafe { ffi::PyDateTime_GET_YEAR(self.0.as_ptr()) as i32 }
+    }
+
+    /// Gets the month (1-12).
+    pub fn month(&self, _py: Python) -> u8 {
+        unsafe { ffi::PyDateTime_GET_MONTH(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the day of the month (1-31).
+    pub fn day(&self, _py: Python) -> u8 {
+        unsafe { ffi::PyDateTime_GET_DAY(self.0.as_ptr()) as u8 }
+    }
+}
+
+/// Represents a Python `datetime.datetime` object.
+pub struct PyDateTime(PyObject);
+datetime_newtype!(PyDateTime, PyDateTime_Check, DateTimeType);
+
+impl PyDateTime {
+    /// Creates a new `datetime.datetime` object.
+    ///
+    /// If `tzinfo` is `None`, the resulting datetime is naive.
+    /// Raises `ValueError` if the date or time is invalid.
+    pub fn new(py: Python, year: i32, month: u8, day: u8,
+               hour: u8, minute: u8, second: u8, microsecond: u32,
+               tzinfo: Option<&PyTzInfo>) -> PyResult<PyDateTime> {
+        try!(check_date(py, year, month, day));
+        try!(check_time(py, hour, minute, second, microsecond));
+        let api = try!(datetime_api(py));
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                (api.DateTime_FromDateAndTime)(
+                    year as c_int, month as c_int, day as c_int,
+                    hour as c_int, minute as c_int, second as c_int, microsecond as c_int,
+                    tzinfo_ptr(tzinfo), api.DateTimeType))
+        }
+    }
+
+    /// Creates a `datetime.datetime` from a POSIX timestamp,
+    /// like `datetime.fromtimestamp(timestamp, tzinfo)`.
+    ///
+    /// If `tzinfo` is `None`, the result is the naive local time.
+    pub fn from_timestamp(py: Python, timestamp: f64, tzinfo: Option<&PyTzInfo>) -> PyResult<PyDateTime> {
+        let args = match tzinfo {
+            Some(tzinfo) => (timestamp, tzinfo).to_py_object(py),
+            None => (timestamp,).to_py_object(py)
+        };
+        try!(datetime_api(py));
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyDateTime_FromTimestamp(args.as_object().as_ptr()))
+        }
+    }
+
+    /// Gets the year.
+    pub fn year(&self, _py: Python) -> i32 {
+        unsafe { ffi::PyDateTime_GET_YEAR(self.0.as_ptr()) as i32 }
+    }
+
+    /// Gets the month (1-12).
+    pub fn month(&self, _py: Python) -> u8 {
+        unsafe { ffi::PyDateTime_GET_MONTH(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the day of the month (1-31).
+    pub fn day(&self, _py: Python) -> u8 {
+        unsafe { ffi::PyDateTime_GET_DAY(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the hour (0-23).
+    pub fn hour(&self, _py: Python) -> u8 {
+        unsafe { ffi::PyDateTime_DATE_GET_HOUR(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the minute (0-59).
+    pub fn minute(&self, _py: Python) -> u8 {
+        unsafe { ffi::PyDateTime_DATE_GET_MINUTE(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the second (0-59).
+    pub fn second(&self, _py: Python) -> u8 {
+        unsafe { ffi::PyDateTime_DATE_GET_SECOND(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the microsecond (0-999999).
+    pub fn microsecond(&self, _py: Python) -> u32 {
+        unsafe { ffi::PyDateTime_DATE_GET_MICROSECOND(self.0.as_ptr()) as u32 }
+    }
+
+    /// Gets the `fold` attribute, which disambiguates repeated wall times.
+    #[cfg(Py_3_6)]
+    pub fn fold(&self, _py: Python) -> bool {
+        unsafe { ffi::PyDateTime_DATE_GET_FOLD(self.0.as_ptr()) != 0 }
+    }
+
+    /// Gets the time zone, or `None` if this datetime is naive.
+    pub fn tzinfo(&self, py: Python) -> Option<PyTzInfo> {
+        unsafe {
+            let obj = &*(self.0.as_ptr() as *mut ffi::PyDateTime_DateTime);
+            get_tzinfo(py, obj.hastzinfo != 0, obj.tzinfo)
+        }
+    }
+}
+
+/// Represents a Python `datetime.time` object.
+pub struct PyTime(PyObject);
+datetime_newtype!(PyTime, PyTime_Check, TimeType);
+
+impl PyTime {
+    /// Creates a new `datetime.time` object.
+    ///
+    /// Raises `ValueError` if the time is invalid.
+    pub fn new(py: Python, hour: u8, minute: u8, second: u8, microsecond: u32,
+               tzinfo: Option<&PyTzInfo>) -> PyResult<PyTime> {
+        try!(check_time(py, hour, minute, second, microsecond));
+        let api = try!(datetime_api(py));
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                (api.Time_FromTime)(
+                    hour as c_int, minute as c_int, second as c_int, microsecond as c_int,
+                    tzinfo_ptr(tzinfo), api.TimeType))
+        }
+    }
+
+    /// Gets the hour (0-23).
+    pub fn hour(&self, _py: Python) -> u8 {
+        unsafe { ffi::PyDateTime_TIME_GET_HOUR(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the minute (0-59).
+    pub fn minute(&self, _py: Python) -> u8 {
+        unsafe { ffi::PyDateTime_TIME_GET_MINUTE(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the second (0-59).
+    pub fn second(&self, _py: Python) -> u8 {
+        unsafe { ffi::PyDateTime_TIME_GET_SECOND(self.0.as_ptr()) as u8 }
+    }
+
+    /// Gets the microsecond (0-999999).
+    pub fn microsecond(&self, _py: Python) -> u32 {
+        unsafe { ffi::PyDateTime_TIME_GET_MICROSECOND(self.0.as_ptr()) as u32 }
+    }
+
+    /// Gets the `fold` attribute, which disambiguates repeated wall times.
+    #[cfg(Py_3_6)]
+    pub fn fold(&self, _py: Python) -> bool {
+        unsafe { ffi::PyDateTime_TIME_GET_FOLD(self.0.as_ptr()) != 0 }
+    }
+
+    /// Gets the time zone, or `None` if this time is naive.
+    pub fn tzinfo(&self, py: Python) -> Option<PyTzInfo> {
+        unsafe {
+            let obj = &*(self.0.as_ptr() as *mut ffi::PyDateTime_Time);
+            get_tzinfo(py, obj.hastzinfo != 0, obj.tzinfo)
+        }
+    }
+}
+
+/// Represents a Python `datetime.timedelta` object.
+///
+/// Python normalizes timedeltas so that only the number of days can be negative;
+/// `seconds` is always in `0..86400` and `microseconds` in `0..1000000`.
+pub struct PyDelta(PyObject);
+datetime_newtype!(PyDelta, PyDelta_Check, DeltaType);
+
+impl PyDelta {
+    /// Creates a new `datetime.timedelta` object.
+    ///
+    /// The seconds and microseconds are normalized into the valid range.
+    /// Raises `OverflowError` if the number of days is out of range.
+    pub fn new(py: Python, days: i32, seconds: i32, microseconds: i32) -> PyResult<PyDelta> {
+        try!(datetime_api(py));
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                ffi::PyDelta_FromDSU(days as c_int, seconds as c_int, microseconds as c_int))
+        }
+    }
+
+    /// Gets the number of days (may be negative).
+    pub fn days(&self, _py: Python) -> i32 {
+        unsafe { ffi::PyDateTime_DELTA_GET_DAYS(self.0.as_ptr()) as i32 }
+    }
+
+    /// Gets the number of seconds (0-86399).
+    pub fn seconds(&self, _py: Python) -> i32 {
+        unsafe { ffi::PyDateTime_DELTA_GET_SECONDS(self.0.as_ptr()) as i32 }
+    }
+
+    /// Gets the number of microseconds (0-999999).
+    pub fn microseconds(&self, _py: Python) -> i32 {
+        unsafe { ffi::PyDateTime_DELTA_GET_MICROSECONDS(self.0.as_ptr()) as i32 }
+    }
+}
+
+/// Represents a Python `datetime.tzinfo` object.
+pub struct PyTzInfo(PyObject);
+datetime_newtype!(PyTzInfo, PyTZInfo_Check, TZInfoType);
+
+impl PyTzInfo {
+    /// Gets the `datetime.timezone.utc` singleton.
+    #[cfg(Py_3_7)]
+    pub fn utc(py: Python) -> PyResult<PyTzInfo> {
+        let api = try!(datetime_api(py));
+        unsafe {
+            Ok(PyObject::from_borrowed_ptr(py, api.TimeZone_UTC).unchecked_cast_into::<PyTzInfo>())
+        }
+    }
+}
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+impl PyDelta {
+    /// Creates a `datetime.timedelta` from a `Duration`.
+    ///
+    /// The duration is truncated to microseconds.
+    /// Raises `OverflowError` if the duration is too large for a `timedelta`.
+    pub fn from_duration(py: Python, duration: Duration) -> PyResult<PyDelta> {
+        let days = duration.as_secs() / SECONDS_PER_DAY;
+        let seconds = duration.as_secs() % SECONDS_PER_DAY;
+        let microseconds = duration.subsec_nanos() / 1000;
+        if days > c_int::max_value() as u64 {
+            return Err(PyErr::new::<exc::OverflowError, _>(py,
+                "Duration is too large to be converted to timedelta"));
+        }
+        PyDelta::new(py, days as i32, seconds as i32, microseconds as i32)
+    }
+}
+
+/// Converts a `Duration` into a `datetime.timedelta`.
+///
+/// The duration is truncated to microseconds.
+/// Panics if the duration is too large for a `timedelta`;
+/// use `PyDelta::from_duration` to handle that case.
+impl ToPyObject for Duration {
+    type ObjectType = PyDelta;
+
+    fn to_py_object(&self, py: Python) -> PyDelta {
+        PyDelta::from_duration(py, *self)
+            .unwrap_or_else(|e| panic!("Duration cannot be converted to timedelta: {:?}", e))
+    }
+}
+
+// Extracts a `Duration` from a `datetime.timedelta`.
+//
+// Raises `ValueError` if the timedelta is negative.
+extract!(obj to Duration; py => {
+    let delta = try!(obj.cast_as::<PyDelta>(py));
+    let days = delta.days(py);
+    if days < 0 {
+        return Err(PyErr::new::<exc::ValueError, _>(py,
+            "negative timedelta cannot be converted to Duration"));
+    }
+    let secs = days as u64 * SECONDS_PER_DAY + delta.seconds(py) as u64;
+    Ok(Duration::new(secs, delta.microseconds(py) as u32 * 1000))
+});
+
+/// Gets the time zone used for converted `SystemTime`s: UTC on Python 3,
+/// and `None` (naive datetimes in UTC) on Python 2.
+#[cfg(all(feature="python3-sys", Py_3_7))]
+fn system_time_tzinfo(py: Python) -> PyResult<Option<PyTzInfo>> {
+    Ok(Some(try!(PyTzInfo::utc(py))))
+}
+
+#[cfg(all(feature="python3-sys", not(Py_3_7)))]
+fn system_time_tzinfo(py: Python) -> PyResult<Option<PyTzInfo>> {
+    use objectprotocol::ObjectProtocol;
+    // Before Python 3.7, `datetime.timezone.utc` is not part of the C-API.
+    // The GIL protects the initialization of `UTC`.
+    static mut UTC: *mut ffi::PyObject = 0 as *mut ffi::PyObject;
+    unsafe {
+        if UTC.is_null() {
+            let timezone = try!(try!(py.import("datetime")).get(py, "timezone"));
+            let utc = try!(try!(timezone.getattr(py, "utc")).cast_into::<PyTzInfo>(py));
+            UTC = utc.into_object().steal_ptr();
+        }
+        Ok(Some(PyObject::from_borrowed_ptr(py, UTC).unchecked_cast_into::<PyTzInfo>()))
+    }
+}
+
+#[cfg(feature="python27-sys")]
+fn system_time_tzinfo(_py: Python) -> PyResult<Option<PyTzInfo>> {
+    Ok(None)
+}
+
+fn unix_epoch(py: Python, tzinfo: Option<&PyTzInfo>) -> PyResult<PyDateTime> {
+    PyDateTime::new(py, 1970, 1, 1, 0, 0, 0, 0, tzinfo)
+}
+
+impl PyDateTime {
+    /// Creates a `datetime.datetime` from a `SystemTime`.
+    ///
+    /// On Python 3, the result is an aware datetime in UTC;
+    /// on Python 2 (which has no UTC time zone object), it is a naive datetime in UTC.
+    /// The time is truncated to microseconds.
+    /// Raises `OverflowError` if the time is out of the range supported by `datetime`.
+    pub fn from_system_time(py: Python, time: SystemTime) -> PyResult<PyDateTime> {
+        let tzinfo = try!(system_time_tzinfo(py));
+        let epoch = try!(unix_epoch(py, tzinfo.as_ref()));
+        let ptr = match time.duration_since(UNIX_EPOCH) {
+            Ok(duration) => unsafe {
+                let delta = try!(PyDelta::from_duration(py, duration));
+                ffi::PyNumber_Add(epoch.as_object().as_ptr(), delta.as_object().as_ptr())
+            },
+            Err(e) => unsafe {
+                let delta = try!(PyDelta::from_duration(py, e.duration()));
+                ffi::PyNumber_Subtract(epoch.as_object().as_ptr(), delta.as_object().as_ptr())
+            }
+        };
+        unsafe { err::result_cast_from_owned_ptr(py, ptr) }
+    }
+}
+
+/// Converts a `SystemTime` into a `datetime.datetime`.
+///
+/// See `PyDateTime::from_system_time` for the details of the conversion.
+/// Panics if the time is out of the range supported by `datetime`;
+/// use `PyDateTime::from_system_time` to handle that case.
+impl ToPyObject for SystemTime {
+    type ObjectType = PyDateTime;
+
+    fn to_py_object(&self, py: Python) -> PyDateTime {
+        PyDateTime::from_system_time(py, *self)
+            .unwrap_or_else(|e| panic!("SystemTime cannot be converted to datetime: {:?}", e))
+    }
+}
+
+fn total_microseconds(py: Python, delta: &PyDelta) -> i64 {
+    (delta.days(py) as i64 * SECONDS_PER_DAY as i64 + delta.seconds(py) as i64) * 1_000_000
+        + delta.microseconds(py) as i64
+}
+
+// Extracts a `SystemTime` from a `datetime.datetime`.
+//
+// Aware datetimes are converted using their `utcoffset()`;
+// naive datetimes are interpreted as UTC.
+// Raises `OverflowError` if the time is out of the range of `SystemTime` on this platform.
+extract!(obj to SystemTime; py => {
+    use objectprotocol::ObjectProtocol;
+    let datetime = try!(obj.cast_as::<PyDateTime>(py));
+    let naive = try!(PyDateTime::new(py, datetime.year(py), datetime.month(py), datetime.day(py),
+        datetime.hour(py), datetime.minute(py), datetime.second(py), datetime.microsecond(py), None));
+    let epoch = try!(unix_epoch(py, None));
+    let delta: PyDelta = unsafe {
+        try!(err::result_cast_from_owned_ptr(py,
+            ffi::PyNumber_Subtract(naive.as_object().as_ptr(), epoch.as_object().as_ptr())))
+    };
+    let mut micros = total_microseconds(py, &delta);
+    let offset = try!(obj.call_method(py, "utcoffset", ::NoArgs, None));
+    if offset != py.None() {
+        micros -= total_microseconds(py, &try!(offset.cast_into::<PyDelta>(py)));
+    }
+    let duration = Duration::new(micros.abs() as u64 / 1_000_000, (micros.abs() % 1_000_000) as u32 * 1000);
+    let time = if micros >= 0 {
+        UNIX_EPOCH.checked_add(duration)
+    } else {
+        UNIX_EPOCH.checked_sub(duration)
+    };
+    match time {
+        Some(time) => Ok(time),
+        None => Err(PyErr::new::<exc::OverflowError, _>(py,
+            "datetime is out of the range supported by SystemTime"))
+    }
+});
+
+#[cfg(feature="chrono")]
+mod chrono_conversions {
+    use super::chrono::{Datelike, Timelike, NaiveDate, NaiveTime, NaiveDateTime};
+    use python::{Python, PythonObject};
+    use conversion::{ToPyObject, FromPyObject};
+    use err::{PyErr, PyResult};
+    use objects::{exc, PyObject};
+    use super::{PyDate, PyTime, PyDateTime};
+
+    /// Gets the microseconds of a chrono time; leap seconds are clamped to the end of the second.
+    fn microsecond<T: Timelike>(time: &T) -> u32 {
+        ::std::cmp::min(time.nanosecond() / 1000, 999_999)
+    }
+
+    /// Converts a `NaiveDate` into a `datetime.date`.
+    ///
+    /// Panics if the year is out of the range supported by `datetime`.
+    impl ToPyObject for NaiveDate {
+        type ObjectType = PyDate;
+
+        fn to_py_object(&self, py: Python) -> PyDate {
+            PyDate::new(py, self.year(), self.month() as u8, self.day() as u8)
+                .unwrap_or_else(|e| panic!("NaiveDate cannot be converted to date: {:?}", e))
+        }
+    }
+
+    /// Converts a `NaiveTime` into a naive `datetime.time`.
+    ///
+    /// The time is truncated to microseconds.
+    impl ToPyObject for NaiveTime {
+        type ObjectType = PyTime;
+
+        fn to_py_object(&self, py: Python) -> PyTime {
+            PyTime::new(py, self.hour() as u8, self.minute() as u8, self.second() as u8,
+                        microsecond(self), None).unwrap()
+        }
+    }
+
+    /// Converts a `NaiveDateTime` into a naive `datetime.datetime`.
+    ///
+    /// The time is truncated to microseconds.
+    /// Panics if the year is out of the range supported by `datetime`.
+    impl ToPyObject for NaiveDateTime {
+        type ObjectType = PyDateTime;
+
+        fn to_py_object(&self, py: Python) -> PyDateTime {
+            PyDateTime::new(py, self.year(), self.month() as u8, self.day() as u8,
+                            self.hour() as u8, self.minute() as u8, self.second() as u8,
+                            microsecond(self), None)
+                .unwrap_or_else(|e| panic!("NaiveDateTime cannot be converted to datetime: {:?}", e))
+        }
+    }
+
+    fn out_of_range(py: Python) -> PyErr {
+        PyErr::new::<exc::ValueError, _>(py, "date or time out of range")
+    }
+
+    extract!(obj to NaiveDate; py => {
+        // `datetime` is a subclass of `date`; its time is ignored.
+        let date = try!(obj.cast_as::<PyDate>(py));
+        NaiveDate::from_ymd_opt(date.year(py), date.month(py) as u32, date.day(py) as u32)
+            .ok_or_else(|| out_of_range(py))
+    });
+
+    // Extracts a `NaiveTime` from a `datetime.time`; the `tzinfo` is ignored.
+    extract!(obj to NaiveTime; py => {
+        let time = try!(obj.cast_as::<PyTime>(py));
+        NaiveTime::from_hms_micro_opt(time.hour(py) as u32, time.minute(py) as u32,
+                                      time.second(py) as u32, time.microsecond(py))
+            .ok_or_else(|| out_of_range(py))
+    });
+
+    // Extracts a `NaiveDateTime` from a `datetime.datetime`; the `tzinfo` is ignored.
+    extract!(obj to NaiveDateTime; py => {
+        let datetime = try!(obj.cast_as::<PyDateTime>(py));
+        let date = try!(NaiveDate::from_ymd_opt(datetime.year(py), datetime.month(py) as u32,
+                                                datetime.day(py) as u32).ok_or_else(|| out_of_range(py)));
+        let time = try!(NaiveTime::from_hms_micro_opt(datetime.hour(py) as u32, datetime.minute(py) as u32,
+                                                      datetime.second(py) as u32, datetime.microsecond(py))
+                            .ok_or_else(|| out_of_range(py)));
+        Ok(NaiveDateTime::new(date, time))
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH, SystemTime};
+    use python::{Python, PythonObject, PythonObjectWithTypeObject};
+    use conversion::ToPyObject;
+    use objects::{PyDate, PyDateTime, PyTime, PyDelta, PyObject};
+
+    fn eval(py: Python, expr: &str) -> PyObject {
+        py.eval(&format!("__import__('datetime').{}", expr), None, None).unwrap()
+    }
+
+    #[test]
+    fn test_date() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let date = PyDate::new(py, 2020, 2, 29).unwrap();
+        assert_eq!((2020, 2, 29), (date.year(py), date.month(py), date.day(py)));
+        assert_eq!("2020-02-29", date.as_object().to_string());
+        assert!(PyDate::new(py, 2021, 2, 29).is_err());
+        assert!(PyDate::type_object(py).as_object() == &eval(py, "date"));
+        // datetime is a subclass of date
+        assert!(eval(py, "datetime(2000, 1, 1)").cast_as::<PyDate>(py).is_ok());
+        assert!(py.eval("1", None, None).unwrap().cast_as::<PyDate>(py).is_err());
+    }
+
+    #[test]
+    fn test_datetime() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dt = PyDateTime::new(py, 1999, 12, 31, 23, 59, 58, 123456, None).unwrap();
+        assert_eq!("1999-12-31 23:59:58.123456", dt.as_object().to_string());
+        assert_eq!((23, 59, 58, 123456), (dt.hour(py), dt.minute(py), dt.second(py), dt.microsecond(py)));
+        assert!(dt.tzinfo(py).is_none());
+        assert!(eval(py, "date(2000, 1, 1)").cast_as::<PyDateTime>(py).is_err());
+
+        let dt = PyDateTime::from_timestamp(py, 86400.5, None).unwrap();
+        assert_eq!(1970, dt.year(py));
+        assert_eq!(500000, dt.microsecond(py));
+    }
+
+    #[test]
+    #[cfg(Py_3_7)]
+    fn test_datetime_tzinfo() {
+        use objects::PyTzInfo;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let utc = PyTzInfo::utc(py).unwrap();
+        let dt = PyDateTime::new(py, 2000, 1, 1, 0, 0, 0, 0, Some(&utc)).unwrap();
+        assert_eq!("2000-01-01 00:00:00+00:00", dt.as_object().to_string());
+        assert!(dt.tzinfo(py).unwrap().as_object() == utc.as_object());
+        let time = PyTime::new(py, 1, 2, 3, 0, Some(&utc)).unwrap();
+        assert!(time.tzinfo(py).is_some());
+    }
+
+    #[test]
+    fn test_time() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let time = PyTime::new(py, 12, 30, 15, 7, None).unwrap();
+        assert_eq!("12:30:15.000007", time.as_object().to_string());
+        assert_eq!((12, 30, 15, 7), (time.hour(py), time.minute(py), time.second(py), time.microsecond(py)));
+        assert!(time.tzinfo(py).is_none());
+        assert!(PyTime::new(py, 24, 0, 0, 0, None).is_err());
+    }
+
+    #[test]
+    fn test_delta() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let delta = PyDelta::new(py, 0, -1, 0).unwrap();
+        assert_eq!((-1, 86399, 0), (delta.days(py), delta.seconds(py), delta.microseconds(py)));
+        assert!(PyDelta::new(py, 1000000000, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_duration() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let duration = Duration::new(2 * 86400 + 3, 4_000_999);
+        let delta = duration.to_py_object(py);
+        assert_eq!((2, 3, 4000), (delta.days(py), delta.seconds(py), delta.microseconds(py)));
+        assert_eq!(Duration::new(2 * 86400 + 3, 4_000_000), delta.as_object().extract::<Duration>(py).unwrap());
+        assert!(eval(py, "timedelta(seconds=-1)").extract::<Duration>(py).is_err());
+        let err = PyDelta::from_duration(py, Duration::from_secs(u64::max_value())).err().unwrap();
+        assert!(err.matches(py, py.get_type::<::objects::exc::OverflowError>()));
+        assert!(PyDelta::from_duration(py, Duration::from_secs(1_000_000_000 * 86400)).is_err());
+    }
+
+    #[test]
+    fn test_system_time() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let time = UNIX_EPOCH + Duration::new(951782400, 500_000_000);
+        let dt = time.to_py_object(py);
+        assert_eq!((2000, 2, 29, 500000), (dt.year(py), dt.month(py), dt.day(py), dt.microsecond(py)));
+        assert_eq!(time, dt.as_object().extract::<SystemTime>(py).unwrap());
+
+        let before = UNIX_EPOCH - Duration::new(1, 250_000_000);
+        let dt = before.to_py_object(py);
+        assert_eq!((1969, 12, 31, 23, 59, 58, 750000),
+                   (dt.year(py), dt.month(py), dt.day(py), dt.hour(py), dt.minute(py), dt.second(py), dt.microsecond(py)));
+        assert_eq!(before, dt.as_object().extract::<SystemTime>(py).unwrap());
+
+        // naive datetimes are interpreted as UTC
+        let naive = eval(py, "datetime(1970, 1, 2)");
+        assert_eq!(UNIX_EPOCH + Duration::from_secs(86400), naive.extract::<SystemTime>(py).unwrap());
+
+        // the extraction doesn't panic even where `SystemTime` can't represent year 1
+        let first = eval(py, "datetime(1, 1, 1)").extract::<SystemTime>(py);
+        if let Err(e) = first {
+            assert!(e.matches(py, py.get_type::<::objects::exc::OverflowError>()));
+        }
+
+        // years after 9999 are out of range
+        let far = UNIX_EPOCH + Duration::from_secs(300_000_000_000);
+        assert!(PyDateTime::from_system_time(py, far).is_err());
+        assert_eq!(dt.as_object().to_string(), PyDateTime::from_system_time(py, before).unwrap().as_object().to_string());
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_system_time_offset() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dt = eval(py, "datetime(1970, 1, 1, 2, tzinfo=__import__('datetime').timezone(__import__('datetime').timedelta(hours=2)))");
+        assert_eq!(UNIX_EPOCH, dt.extract::<SystemTime>(py).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature="chrono")]
+    fn test_chrono() {
+        use objects::datetime::chrono::{NaiveDate, NaiveTime, NaiveDateTime};
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let date = NaiveDate::from_ymd_opt(2020, 2, 29).unwrap();
+        assert_eq!("2020-02-29", date.to_py_object(py).as_object().to_string());
+        assert_eq!(date, eval(py, "date(2020, 2, 29)").extract::<NaiveDate>(py).unwrap());
+
+        let time = NaiveTime::from_hms_micro_opt(1, 2, 3, 4).unwrap();
+        assert_eq!("01:02:03.000004", time.to_py_object(py).as_object().to_string());
+        assert_eq!(time, eval(py, "time(1, 2, 3, 4)").extract::<NaiveTime>(py).unwrap());
+
+        let datetime = NaiveDateTime::new(date, time);
+        assert_eq!("2020-02-29 01:02:03.000004", datetime.to_py_object(py).as_object().to_string());
+        assert_eq!(datetime, eval(py, "datetime(2020, 2, 29, 1, 2, 3, 4)").extract::<NaiveDateTime>(py).unwrap());
+    }
+}
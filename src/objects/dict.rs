@@ -17,9 +17,10 @@
 // DEALINGS IN THE SOFTWARE.
 
 use ffi;
-use python::{Python, PythonObject};
-use conversion::ToPyObject;
-use objects::{PyObject, PyList};
+use python::{Python, PythonObject, PythonObjectDowncastError};
+use conversion::{ToPyObject, FromPyObject};
+use objects::{PyObject, PyList, PyString};
+use objectprotocol::ObjectProtocol;
 use err::{self, PyResult, PyErr};
 use std::{mem, collections, hash, cmp};
 
@@ -156,12 +157,242 @@ impl <K, V> ToPyObject for collections::BTreeMap<K, V>
     }
 }
 
+/// Conversion of key-value pairs into a new Python `dict`.
+///
+/// This is mainly useful for passing keyword arguments to `ObjectProtocol::call()`
+/// and `call_method()`; see also the `py_kwargs!` macro.
+///
+/// # Example
+/// ```
+/// use cpython::{Python, IntoPyDict, ObjectProtocol, NoArgs};
+///
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let int = py.eval("int", None, None).unwrap();
+/// let kwargs = [("base", 16)].into_py_dict(py).unwrap();
+/// let value: i32 = int.call(py, ("ff",), Some(&kwargs)).unwrap().extract(py).unwrap();
+/// assert_eq!(value, 255);
+/// ```
+pub trait IntoPyDict {
+    /// Creates a new `PyDict` containing the key-value pairs.
+    ///
+    /// Raises `TypeError` if a key is not hashable.
+    fn into_py_dict(self, py: Python) -> PyResult<PyDict>;
+}
+
+impl <'a, K, V> IntoPyDict for &'a [(K, V)] where K: ToPyObject, V: ToPyObject {
+    fn into_py_dict(self, py: Python) -> PyResult<PyDict> {
+        dict_from_pairs(py, self.iter().map(|&(ref key, ref value)| (key, value)))
+    }
+}
+
+impl <K, V> IntoPyDict for Vec<(K, V)> where K: ToPyObject, V: ToPyObject {
+    fn into_py_dict(self, py: Python) -> PyResult<PyDict> {
+        dict_from_pairs(py, self)
+    }
+}
+
+impl <K, V, S> IntoPyDict for collections::HashMap<K, V, S> where K: ToPyObject, V: ToPyObject {
+    fn into_py_dict(self, py: Python) -> PyResult<PyDict> {
+        dict_from_pairs(py, self)
+    }
+}
+
+impl <'a, K, V, S> IntoPyDict for &'a collections::HashMap<K, V, S> where K: ToPyObject, V: ToPyObject {
+    fn into_py_dict(self, py: Python) -> PyResult<PyDict> {
+        dict_from_pairs(py, self)
+    }
+}
+
+impl <K, V> IntoPyDict for collections::BTreeMap<K, V> where K: ToPyObject, V: ToPyObject {
+    fn into_py_dict(self, py: Python) -> PyResult<PyDict> {
+        dict_from_pairs(py, self)
+    }
+}
+
+impl <'a, K, V> IntoPyDict for &'a collections::BTreeMap<K, V> where K: ToPyObject, V: ToPyObject {
+    fn into_py_dict(self, py: Python) -> PyResult<PyDict> {
+        dict_from_pairs(py, self)
+    }
+}
+
+fn dict_from_pairs<I, K, V>(py: Python, pairs: I) -> PyResult<PyDict>
+    where I: IntoIterator<Item=(K, V)>, K: ToPyObject, V: ToPyObject
+{
+    let dict = PyDict::new(py);
+    for (key, value) in pairs {
+        try!(dict.set_item(py, key, value));
+    }
+    Ok(dict)
+}
+
+/// Creates a `PyDict` of keyword arguments.
+///
+/// Syntax: `py_kwargs!(py, name1 = value1, name2 = value2, ...)`
+///
+/// The values can be of any type implementing `ToPyObject`.
+/// The macro evaluates to a `PyResult<PyDict>`.
+///
+/// # Example
+/// ```
+/// #[macro_use] extern crate cpython;
+/// use cpython::{Python, ObjectProtocol, NoArgs};
+///
+/// # fn main() {
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let list = py.eval("[1, 3, 2]", None, None).unwrap();
+/// let kwargs = py_kwargs!(py, reverse = true).unwrap();
+/// list.call_method(py, "sort", NoArgs, Some(&kwargs)).unwrap();
+/// assert_eq!(list.extract::<Vec<i32>>(py).unwrap(), vec![3, 2, 1]);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! py_kwargs {
+    ($py:expr) => {
+        Ok::<_, $crate::PyErr>($crate::PyDict::new($py))
+    };
+    ($py:expr, $( $name:ident = $value:expr ),+ ,) => {
+        py_kwargs!($py, $( $name = $value ),+)
+    };
+    ($py:expr, $( $name:ident = $value:expr ),+) => {{
+        let py: $crate::Python = $py;
+        let dict = $crate::PyDict::new(py);
+        let mut result: $crate::PyResult<()> = Ok(());
+        $(
+            if result.is_ok() {
+                result = dict.set_item(py, stringify!($name), $value);
+            }
+        )+
+        result.map(|()| dict)
+    }};
+}
+
+/// Returns the (key, value) pairs of a mapping object.
+///
+/// Exact dicts are read directly; other mappings are accepted if they
+/// provide an `items()` method.
+fn extract_items(py: Python, obj: &PyObject) -> PyResult<Vec<(PyObject, PyObject)>> {
+    if let Ok(dict) = obj.cast_as::<PyDict>(py) {
+        return Ok(dict.items(py));
+    }
+    if unsafe { ffi::PyMapping_Check(obj.as_ptr()) } == 0 || !try!(obj.hasattr(py, "items")) {
+        return Err(PyErr::from(PythonObjectDowncastError(py)));
+    }
+    let mut items = Vec::new();
+    for item in try!(try!(obj.call_method(py, "items", ::NoArgs, None)).iter(py)) {
+        let (key, value) = try!(try!(item).extract::<(PyObject, PyObject)>(py));
+        items.push((key, value));
+    }
+    Ok(items)
+}
+
+/// Adds the failing key to an extraction error: `"{context} {repr(key)}: {message}"`.
+fn add_key_context(py: Python, err: PyErr, context: &str, key: &PyObject) -> PyErr {
+    let key = match key.repr(py) {
+        Ok(repr) => repr.to_string_lossy(py).into_owned(),
+        Err(_) => String::from("<unrepresentable key>")
+    };
+    err::add_context(py, err, &format!("{} {}", context, key))
+}
+
+fn extract_pair<'a, K, V>(py: Python, key: &'a PyObject, value: &'a PyObject) -> PyResult<(K, V)>
+    where K: FromPyObject<'a>, V: FromPyObject<'a>
+{
+    let k = match key.extract::<K>(py) {
+        Ok(k) => k,
+        Err(e) => return Err(add_key_context(py, e, "failed to extract key", key))
+    };
+    let v = match value.extract::<V>(py) {
+        Ok(v) => v,
+        Err(e) => return Err(add_key_context(py, e, "failed to extract value for key", key))
+    };
+    Ok((k, v))
+}
+
+/// Extracts a `HashMap` from any Python mapping.
+///
+/// If a key or value fails to convert, the error message names the offending key.
+impl <'source, K, V> FromPyObject<'source> for collections::HashMap<K, V>
+    where for<'a> K: FromPyObject<'a> + hash::Hash + cmp::Eq,
+          for<'a> V: FromPyObject<'a>
+{
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        let items = try!(extract_items(py, obj));
+        let mut map = collections::HashMap::with_capacity(items.len());
+        for &(ref key, ref value) in &items {
+            let (k, v) = try!(extract_pair::<K, V>(py, key, value));
+            map.insert(k, v);
+        }
+        Ok(map)
+    }
+}
+
+/// Extracts a `BTreeMap` from any Python mapping.
+///
+/// If a key or value fails to convert, the error message names the offending key.
+impl <'source, K, V> FromPyObject<'source> for collections::BTreeMap<K, V>
+    where for<'a> K: FromPyObject<'a> + cmp::Ord,
+          for<'a> V: FromPyObject<'a>
+{
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        let items = try!(extract_items(py, obj));
+        let mut map = collections::BTreeMap::new();
+        for &(ref key, ref value) in &items {
+            let (k, v) = try!(extract_pair::<K, V>(py, key, value));
+            map.insert(k, v);
+        }
+        Ok(map)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use python::{Python, PythonObject};
     use conversion::ToPyObject;
-    use objects::{PyDict, PyTuple};
-    use std::collections::HashMap;
+    use objects::{PyDict, PyList, PyTuple, PyObject, exc};
+    use objectprotocol::ObjectProtocol;
+    use super::IntoPyDict;
+    use std::collections::{HashMap, BTreeMap};
+
+    #[test]
+    fn test_into_py_dict() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = [("a", 1), ("b", 2)].into_py_dict(py).unwrap();
+        assert_eq!(2, dict.len(py));
+        assert_eq!(2, dict.get_item(py, "b").unwrap().extract::<i32>(py).unwrap());
+        let pairs: &[(&str, &str)] = &[("x", "y")];
+        assert_eq!("y", pairs.into_py_dict(py).unwrap().get_item(py, "x").unwrap().extract::<String>(py).unwrap());
+        assert_eq!(1, vec![(1, 2.5)].into_py_dict(py).unwrap().len(py));
+
+        let mut map = HashMap::new();
+        map.insert("k", vec![1, 2]);
+        assert_eq!(1, (&map).into_py_dict(py).unwrap().len(py));
+        assert_eq!(vec![1, 2], map.into_py_dict(py).unwrap().get_item(py, "k").unwrap().extract::<Vec<i32>>(py).unwrap());
+        let mut map = BTreeMap::new();
+        map.insert(3, "three");
+        assert_eq!("three", map.into_py_dict(py).unwrap().get_item(py, 3).unwrap().extract::<String>(py).unwrap());
+
+        let unhashable = vec![(PyList::new(py, &[]), 1)];
+        let err = unhashable.into_py_dict(py).err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>()));
+    }
+
+    #[test]
+    fn test_py_kwargs() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        assert_eq!(0, py_kwargs!(py).unwrap().len(py));
+        let kwargs = py_kwargs!(py, a = 1, b = "x",).unwrap();
+        assert_eq!(2, kwargs.len(py));
+        assert_eq!(1, kwargs.get_item(py, "a").unwrap().extract::<i32>(py).unwrap());
+        assert_eq!("x", kwargs.get_item(py, "b").unwrap().extract::<String>(py).unwrap());
+
+        let dict_type = py.get_type::<PyDict>();
+        let result = dict_type.call(py, ::objects::NoArgs, Some(&py_kwargs!(py, key = 42).unwrap())).unwrap();
+        assert_eq!(42, result.get_item(py, "key").unwrap().extract::<i32>(py).unwrap());
+    }
 
     #[test]
     fn test_len() {
@@ -288,4 +519,69 @@ mod test {
         assert_eq!(32 + 42 + 123, value_sum);
     }
 
+    #[test]
+    fn test_extract_hashmap() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = HashMap::new();
+        v.insert(7, 32);
+        v.insert(8, 42);
+        let dict = v.to_py_object(py).into_object();
+        assert_eq!(v, dict.extract::<HashMap<i32, i32>>(py).unwrap());
+        let btree = dict.extract::<BTreeMap<i32, i32>>(py).unwrap();
+        assert_eq!(vec![(7, 32), (8, 42)], btree.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_extract_from_mapping() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mapping = py.eval("type('Mapping', (object,), {
+                '__getitem__': lambda self, key: {'a': 1, 'b': 2}[key],
+                '__len__': lambda self: 2,
+                'items': lambda self: [('a', 1), ('b', 2)],
+            })()", None, None).unwrap();
+        assert!(mapping.cast_as::<PyDict>(py).is_err());
+        let map = mapping.extract::<BTreeMap<String, i32>>(py).unwrap();
+        assert_eq!(vec![("a".to_owned(), 1), ("b".to_owned(), 2)], map.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_extract_not_a_mapping() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let list = py.eval("[(1, 2)]", None, None).unwrap();
+        let err = list.extract::<HashMap<i32, i32>>(py).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>()));
+    }
+
+    #[test]
+    fn test_extract_reports_failing_entry() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict: PyObject = py.eval("{'a': 1, 'b': 'x'}", None, None).unwrap();
+        let mut err = dict.extract::<HashMap<String, i32>>(py).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>()));
+        let message = err.instance(py).to_string();
+        assert!(message.starts_with("failed to extract value for key 'b'"), "{}", message);
+
+        let dict: PyObject = py.eval("{1: 1}", None, None).unwrap();
+        let mut err = dict.extract::<HashMap<String, i32>>(py).unwrap_err();
+        let message = err.instance(py).to_string();
+        assert!(message.starts_with("failed to extract key 1"), "{}", message);
+    }
+
+    #[cfg(feature="python3-sys")]
+    #[test]
+    fn test_extract_wraps_unicode_errors() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict: PyObject = py.eval("{'a': '\\ud800'}", None, None).unwrap();
+        let mut err = dict.extract::<HashMap<String, String>>(py).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::ValueError>()));
+        let instance = err.instance(py);
+        assert!(instance.to_string().starts_with("failed to extract value for key 'a': "));
+        let cause = instance.getattr(py, "__cause__").unwrap();
+        assert!(cause.get_type(py) == py.get_type::<exc::UnicodeEncodeError>());
+    }
 }
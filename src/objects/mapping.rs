@@ -0,0 +1,182 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use ffi;
+use python::{Python, PythonObject, ToPythonPointer};
+use conversion::ToPyObject;
+use objects::{PyObject, PyList};
+use err::{self, PyErr, PyResult};
+
+/// Represents a reference to a python object supporting the mapping protocol.
+///
+/// Note that Python considers every object that supports `o[key]` to be a mapping,
+/// so sequences like `list` can also be cast to `PyMapping`.
+pub struct PyMapping(PyObject);
+
+pyobject_newtype!(PyMapping, PyMapping_Check);
+
+impl PyMapping {
+    /// Returns the number of keys in the mapping. This is equivalent to Python `len()`.
+    #[inline]
+    pub fn len(&self, py: Python) -> PyResult<usize> {
+        let v = unsafe { ffi::PyMapping_Size(self.0.as_ptr()) };
+        if v == -1 {
+            Err(PyErr::fetch(py))
+        } else {
+            Ok(v as usize)
+        }
+    }
+
+    /// Gets the value for `key`. This is equivalent to the Python expression `o[key]`.
+    #[inline]
+    pub fn get_item<K>(&self, py: Python, key: K) -> PyResult<PyObject> where K: ToPyObject {
+        key.with_borrowed_ptr(py, |key| unsafe {
+            err::result_from_owned_ptr(py, ffi::PyObject_GetItem(self.as_ptr(), key))
+        })
+    }
+
+    /// Sets the value for `key`. This is equivalent to the Python statement `o[key] = value`.
+    #[inline]
+    pub fn set_item<K, V>(&self, py: Python, key: K, value: V) -> PyResult<()>
+        where K: ToPyObject, V: ToPyObject
+    {
+        key.with_borrowed_ptr(py, move |key|
+            value.with_borrowed_ptr(py, |value| unsafe {
+                err::error_on_minusone(py, ffi::PyObject_SetItem(self.as_ptr(), key, value))
+            }))
+    }
+
+    /// Deletes the value for `key`. This is equivalent to the Python statement `del o[key]`.
+    #[inline]
+    pub fn del_item<K>(&self, py: Python, key: K) -> PyResult<()> where K: ToPyObject {
+        key.with_borrowed_ptr(py, |key| unsafe {
+            err::error_on_minusone(py, ffi::PyMapping_DelItem(self.as_ptr(), key))
+        })
+    }
+
+    /// Determines whether the mapping contains `key`.
+    /// This is equivalent to the Python expression `key in o`.
+    ///
+    /// Unlike `PyMapping_HasKey`, this reports exceptions raised by `__contains__`.
+    #[inline]
+    pub fn contains<K>(&self, py: Python, key: K) -> PyResult<bool> where K: ToPyObject {
+        let r = key.with_borrowed_ptr(py, |key| unsafe {
+            ffi::PySequence_Contains(self.as_ptr(), key)
+        });
+        match r {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(PyErr::fetch(py))
+        }
+    }
+
+    /// Returns a list of the keys in the mapping.
+    /// This is equivalent to the Python expression `list(o.keys())`.
+    #[inline]
+    pub fn keys(&self, py: Python) -> PyResult<PyList> {
+        unsafe { to_list(py, ffi::PyMapping_Keys(self.as_ptr())) }
+    }
+
+    /// Returns a list of the values in the mapping.
+    /// This is equivalent to the Python expression `list(o.values())`.
+    #[inline]
+    pub fn values(&self, py: Python) -> PyResult<PyList> {
+        unsafe { to_list(py, ffi::PyMapping_Values(self.as_ptr())) }
+    }
+
+    /// Returns a list of the `(key, value)` tuples in the mapping.
+    /// This is equivalent to the Python expression `list(o.items())`.
+    #[inline]
+    pub fn items(&self, py: Python) -> PyResult<PyList> {
+        unsafe { to_list(py, ffi::PyMapping_Items(self.as_ptr())) }
+    }
+}
+
+/// Converts the result of `PyMapping_Keys()` etc. into a list.
+///
+/// Before Python 3.7, these functions return whatever the `keys()` method returns,
+/// which is usually a view object.
+unsafe fn to_list(py: Python, ptr: *mut ffi::PyObject) -> PyResult<PyList> {
+    let obj = try!(err::result_from_owned_ptr(py, ptr));
+    if ffi::PyList_CheckExact(obj.as_ptr()) != 0 {
+        Ok(obj.unchecked_cast_into())
+    } else {
+        err::result_cast_from_owned_ptr(py, ffi::PySequence_List(obj.as_ptr()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objectprotocol::ObjectProtocol;
+    use objects::{PyDict, PyMapping};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_numbers_are_not_mappings() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        assert!(42i32.to_py_object(py).into_object().cast_into::<PyMapping>(py).is_err());
+    }
+
+    #[test]
+    fn test_dict_as_mapping() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut map = HashMap::new();
+        map.insert(1, 2);
+        let mapping = map.to_py_object(py).into_object().cast_into::<PyMapping>(py).unwrap();
+        assert_eq!(mapping.len(py).unwrap(), 1);
+        assert_eq!(mapping.get_item(py, 1).unwrap().extract::<i32>(py).unwrap(), 2);
+        assert!(mapping.get_item(py, 2).is_err());
+        mapping.set_item(py, 3, 4).unwrap();
+        assert!(mapping.contains(py, 3).unwrap());
+        mapping.del_item(py, 1).unwrap();
+        assert!(!mapping.contains(py, 1).unwrap());
+        assert!(mapping.del_item(py, 1).is_err());
+        assert_eq!(mapping.keys(py).unwrap().into_object().extract::<Vec<i32>>(py).unwrap(), vec![3]);
+        assert_eq!(mapping.values(py).unwrap().into_object().extract::<Vec<i32>>(py).unwrap(), vec![4]);
+        assert_eq!(mapping.items(py).unwrap().into_object().extract::<Vec<(i32, i32)>>(py).unwrap(), vec![(3, 4)]);
+    }
+
+    #[test]
+    fn test_custom_mapping() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let dict = PyDict::new(py);
+        dict.set_item(py, "a", 1).unwrap();
+        #[cfg(feature="python3-sys")]
+        let code = "__import__('types').MappingProxyType";
+        #[cfg(feature="python27-sys")]
+        let code = "lambda d: type('M', (__import__('UserDict').DictMixin, object), { \
+            'keys': lambda self: d.keys(), '__getitem__': lambda self, k: d[k], \
+            '__len__': lambda self: len(d)})()";
+        let factory = py.eval(code, None, None).unwrap();
+        let mapping = factory.call(py, (dict,), None).unwrap().cast_into::<PyMapping>(py).unwrap();
+        assert_eq!(mapping.len(py).unwrap(), 1);
+        assert!(mapping.contains(py, "a").unwrap());
+        assert!(!mapping.contains(py, "b").unwrap());
+        assert_eq!(mapping.get_item(py, "a").unwrap().extract::<i32>(py).unwrap(), 1);
+        assert_eq!(mapping.keys(py).unwrap().into_object().extract::<Vec<String>>(py).unwrap(), vec!["a"]);
+        assert_eq!(mapping.items(py).unwrap().into_object().extract::<Vec<(String, i32)>>(py).unwrap(),
+                   vec![("a".to_owned(), 1)]);
+        #[cfg(feature="python3-sys")]
+        assert!(mapping.set_item(py, "b", 2).is_err());
+    }
+}
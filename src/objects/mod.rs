@@ -20,7 +20,10 @@ pub use self::object::PyObject;
 pub use self::typeobject::PyType;
 pub use self::module::PyModule;
 
-pub use self::string::{PyBytes, PyString, PyStringData};
+pub use self::string::{PyBytes, PyString, PyStringData, InternedString};
+#[doc(hidden)]
+pub use self::string::get_interned;
+pub use self::bytearray::PyByteArray;
 #[cfg(feature="python27-sys")]
 pub use self::string::PyUnicode;
 #[cfg(feature="python3-sys")]
@@ -29,14 +32,25 @@ pub use self::string::PyString as PyUnicode;
 pub use self::iterator::PyIterator;
 pub use self::boolobject::PyBool;
 pub use self::tuple::{PyTuple, NoArgs};
-pub use self::dict::PyDict;
+pub use self::dict::{PyDict, IntoPyDict};
 pub use self::list::PyList;
+pub use self::set::{PySet, PyFrozenSet};
 #[cfg(feature="python27-sys")]
 pub use self::num::PyInt;
 #[cfg(feature="python3-sys")]
 pub use self::num::PyLong as PyInt;
 pub use self::num::{PyLong, PyFloat};
+pub use self::complex::PyComplex;
 pub use self::sequence::PySequence;
+pub use self::mapping::PyMapping;
+pub use self::slice::{PySlice, PySliceIndices, SliceOrIndex};
+pub use self::capsule::PyCapsule;
+pub use self::cfunction::PyCFunction;
+pub use self::weakref::{PyWeakRef, PyWeakProxy};
+pub use self::traceback::{PyTraceback, PyTracebackFrame, PyTracebackFrames};
+pub use self::datetime::{PyDate, PyDateTime, PyTime, PyDelta, PyTzInfo};
+#[cfg(feature="python3-sys")]
+pub use self::path::PathlibPath;
 
 #[macro_export]
 macro_rules! pyobject_newtype(
@@ -125,13 +139,24 @@ mod object;
 mod typeobject;
 mod module;
 mod string;
+mod bytearray;
 mod dict;
 mod iterator;
 mod boolobject;
 mod tuple;
 mod list;
+mod complex;
+mod set;
 mod num;
 mod sequence;
+mod mapping;
+mod slice;
+mod capsule;
+mod cfunction;
+mod weakref;
+mod traceback;
+mod path;
+mod datetime;
 pub mod exc;
 
 #[cfg(feature="python27-sys")]
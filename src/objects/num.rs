@@ -17,9 +17,11 @@
 // DEALINGS IN THE SOFTWARE.
 
 extern crate num_traits;
+#[cfg(feature="num-bigint")]
+extern crate num_bigint;
 
 use self::num_traits::cast::cast;
-use libc::{c_long, c_double};
+use libc::{c_long, c_double, c_int, size_t};
 use python::{Python, PythonObject, PyClone};
 use err::{self, PyResult, PyErr};
 use super::object::PyObject;
@@ -268,6 +270,125 @@ int_fits_larger_int!(usize, u64);
 // u64 has a manual implementation as it never fits into signed long
 int_convert_u64_or_i64!(u64, ffi::PyLong_FromUnsignedLongLong, ffi::PyLong_AsUnsignedLongLong);
 
+/// Converts `obj` to a Python `long`, calling `__int__` if necessary.
+#[cfg(any(feature="i128", feature="num-bigint"))]
+fn to_long(py: Python, obj: &PyObject) -> PyResult<PyObject> {
+    unsafe {
+        if ffi::PyLong_Check(obj.as_ptr()) != 0 {
+            Ok(obj.clone_ref(py))
+        } else {
+            err::result_from_owned_ptr(py, ffi::PyNumber_Long(obj.as_ptr()))
+        }
+    }
+}
+
+/// Creates a Python `long` from its little-endian byte representation.
+#[cfg(any(feature="i128", feature="num-bigint"))]
+fn long_from_le_bytes(py: Python, bytes: &[u8], is_signed: bool) -> PyObject {
+    unsafe {
+        err::from_owned_ptr_or_panic(py,
+            ffi::_PyLong_FromByteArray(bytes.as_ptr(), bytes.len() as size_t, 1, is_signed as c_int))
+    }
+}
+
+/// Writes the little-endian byte representation of the Python `long` `obj` into `bytes`.
+///
+/// Fails with `OverflowError` if the value does not fit.
+#[cfg(any(feature="i128", feature="num-bigint"))]
+fn long_to_le_bytes(py: Python, obj: &PyObject, bytes: &mut [u8], is_signed: bool) -> PyResult<()> {
+    unsafe {
+        err::error_on_minusone(py, ffi::_PyLong_AsByteArray(
+            obj.as_ptr() as *mut ffi::PyLongObject,
+            bytes.as_mut_ptr(), bytes.len() as size_t, 1, is_signed as c_int))
+    }
+}
+
+#[cfg(feature="i128")]
+macro_rules! int_convert_128 (
+    ($rust_type:ty, $is_signed:expr) => (
+        impl ToPyObject for $rust_type {
+            #[cfg(feature="python27-sys")]
+            type ObjectType = PyObject;
+
+            #[cfg(feature="python3-sys")]
+            type ObjectType = PyLong;
+
+            #[cfg(feature="python27-sys")]
+            fn to_py_object(&self, py: Python) -> PyObject {
+                match cast::<$rust_type, c_long>(*self) {
+                    Some(v) => PyInt::new(py, v).into_object(),
+                    None => long_from_le_bytes(py, &self.to_le_bytes(), $is_signed)
+                }
+            }
+
+            #[cfg(feature="python3-sys")]
+            fn to_py_object(&self, py: Python) -> PyLong {
+                unsafe { long_from_le_bytes(py, &self.to_le_bytes(), $is_signed).unchecked_cast_into() }
+            }
+        }
+
+        extract!(obj to $rust_type; py => {
+            let num = try!(to_long(py, obj));
+            let mut bytes = [0; 16];
+            try!(long_to_le_bytes(py, &num, &mut bytes, $is_signed));
+            Ok(<$rust_type>::from_le_bytes(bytes))
+        });
+    )
+);
+
+#[cfg(feature="i128")]
+int_convert_128!(i128, true);
+#[cfg(feature="i128")]
+int_convert_128!(u128, false);
+
+#[cfg(feature="num-bigint")]
+mod bigint_conversions {
+    use super::num_bigint::{BigInt, BigUint};
+    use ffi;
+    use python::{Python, PythonObject};
+    use err::{PyResult, PyErr};
+    use objects::PyObject;
+    use conversion::ToPyObject;
+    use super::{to_long, long_from_le_bytes, long_to_le_bytes};
+
+    impl ToPyObject for BigInt {
+        type ObjectType = PyObject;
+
+        fn to_py_object(&self, py: Python) -> PyObject {
+            long_from_le_bytes(py, &self.to_signed_bytes_le(), true)
+        }
+    }
+
+    impl ToPyObject for BigUint {
+        type ObjectType = PyObject;
+
+        fn to_py_object(&self, py: Python) -> PyObject {
+            long_from_le_bytes(py, &self.to_bytes_le(), false)
+        }
+    }
+
+    /// Number of bytes required to represent the Python `long` `num`.
+    fn byte_len(num: &PyObject, is_signed: bool) -> usize {
+        let bits = unsafe { ffi::_PyLong_NumBits(num.as_ptr()) };
+        // signed representations need room for the sign bit
+        (bits + is_signed as usize) / 8 + 1
+    }
+
+    extract!(obj to BigInt; py => {
+        let num = try!(to_long(py, obj));
+        let mut bytes = vec![0; byte_len(&num, true)];
+        try!(long_to_le_bytes(py, &num, &mut bytes, true));
+        Ok(BigInt::from_signed_bytes_le(&bytes))
+    });
+
+    extract!(obj to BigUint; py => {
+        let num = try!(to_long(py, obj));
+        let mut bytes = vec![0; byte_len(&num, false)];
+        try!(long_to_le_bytes(py, &num, &mut bytes, false));
+        Ok(BigUint::from_bytes_le(&bytes))
+    });
+}
+
 impl ToPyObject for f64 {
     type ObjectType = PyFloat;
 
@@ -306,6 +427,7 @@ mod test {
     use std;
     use python::{Python, PythonObject};
     use conversion::ToPyObject;
+    use objects::exc;
 
     macro_rules! num_to_py_object_and_back (
         ($func_name:ident, $t1:ty, $t2:ty) => (
@@ -381,4 +503,49 @@ mod test {
         assert_eq!(v, obj.extract::<u64>(py).unwrap());
         assert!(obj.extract::<i64>(py).is_err());
     }
+
+    #[cfg(feature="i128")]
+    num_to_py_object_and_back!(to_from_i128, i128, i128);
+    #[cfg(feature="i128")]
+    num_to_py_object_and_back!(to_from_u128, u128, u128);
+    #[cfg(feature="i128")]
+    num_to_py_object_and_back!(float_to_i128, f64, i128);
+
+    #[test]
+    #[cfg(feature="i128")]
+    fn test_i128_limits() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let v = std::i128::MIN;
+        let obj = v.to_py_object(py).into_object();
+        assert_eq!(v, obj.extract::<i128>(py).unwrap());
+        assert!(obj.extract::<u128>(py).is_err());
+        let v = std::u128::MAX;
+        let obj = v.to_py_object(py).into_object();
+        assert_eq!(v, obj.extract::<u128>(py).unwrap());
+        assert!(obj.extract::<i128>(py).is_err());
+        assert_eq!("340282366920938463463374607431768211455", obj.to_string());
+        let obj = py.eval("2 ** 128", None, None).unwrap();
+        assert!(obj.extract::<u128>(py).unwrap_err().matches(py, py.get_type::<exc::OverflowError>()));
+    }
+
+    #[test]
+    #[cfg(feature="num-bigint")]
+    fn test_bigint() {
+        use objects::num::num_bigint::{BigInt, BigUint};
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        for expr in &["0", "1", "-1", "255", "-256", "2 ** 200 + 12345", "-(3 ** 150)"] {
+            let obj = py.eval(expr, None, None).unwrap();
+            let value = obj.extract::<BigInt>(py).unwrap();
+            assert_eq!(obj.to_string(), value.to_string());
+            assert_eq!(obj.to_string(), value.to_py_object(py).to_string());
+        }
+        let obj = py.eval("2 ** 200 + 12345", None, None).unwrap();
+        let value = obj.extract::<BigUint>(py).unwrap();
+        assert_eq!(obj.to_string(), value.to_string());
+        assert_eq!(obj.to_string(), value.to_py_object(py).to_string());
+        assert!(py.eval("-1", None, None).unwrap().extract::<BigUint>(py).is_err());
+    }
 }
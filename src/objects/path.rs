@@ -0,0 +1,262 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use ffi;
+use python::{Python, PythonObject};
+use conversion::{ToPyObject, FromPyObject};
+use err::{self, PyResult};
+use super::{PyObject, PyBytes, PyString};
+#[cfg(not(Py_3_6))]
+use python::PyClone;
+#[cfg(not(Py_3_6))]
+use objectprotocol::ObjectProtocol;
+
+/// Calls `__fspath__` on `os.PathLike` objects; other objects are returned unchanged.
+///
+/// Python 3.6 and later do this as part of `PyUnicode_FSConverter`.
+#[cfg(not(Py_3_6))]
+fn fspath(py: Python, obj: &PyObject) -> PyResult<PyObject> {
+    if try!(obj.get_type(py).as_object().hasattr(py, "__fspath__")) {
+        obj.call_method(py, "__fspath__", ::NoArgs, None)
+    } else {
+        Ok(obj.clone_ref(py))
+    }
+}
+
+/// Converts a `str`, `bytes` or `os.PathLike` object into the `bytes`
+/// representation of the path, using the file system encoding
+/// with the `surrogateescape` error handler.
+#[cfg(feature="python3-sys")]
+fn fs_encode(py: Python, obj: &PyObject) -> PyResult<PyBytes> {
+    #[cfg(not(Py_3_6))]
+    let obj = &try!(fspath(py, obj));
+    let mut result: *mut ffi::PyObject = ::std::ptr::null_mut();
+    unsafe {
+        if ffi::PyUnicode_FSConverter(obj.as_ptr(), &mut result as *mut _ as *mut ::libc::c_void) == 0 {
+            return Err(::err::PyErr::fetch(py));
+        }
+        err::result_cast_from_owned_ptr(py, result)
+    }
+}
+
+/// Converts a `str`, `unicode` or path-like object into the `str`
+/// representation of the path, using the file system encoding.
+#[cfg(feature="python27-sys")]
+fn fs_encode(py: Python, obj: &PyObject) -> PyResult<PyBytes> {
+    let obj = try!(fspath(py, obj));
+    if let Ok(bytes) = obj.cast_as::<PyBytes>(py) {
+        return Ok(bytes.clone_ref(py));
+    }
+    let unicode = try!(obj.cast_as::<::objects::PyUnicode>(py));
+    unsafe {
+        let encoding = if ffi::Py_FileSystemDefaultEncoding.is_null() {
+            cstr!("utf-8").as_ptr()
+        } else {
+            ffi::Py_FileSystemDefaultEncoding
+        };
+        err::result_cast_from_owned_ptr(py,
+            ffi::PyUnicode_AsEncodedString(unicode.as_object().as_ptr(), encoding, cstr!("strict").as_ptr()))
+    }
+}
+
+/// Decodes the file system representation of a path into a `str`,
+/// using the file system encoding with the `surrogateescape` error handler.
+#[cfg(feature="python3-sys")]
+fn fs_decode(py: Python, path: &[u8]) -> PyString {
+    unsafe {
+        err::cast_from_owned_ptr_or_panic(py, ffi::PyUnicode_DecodeFSDefaultAndSize(
+            path.as_ptr() as *const _, path.len() as ffi::Py_ssize_t))
+    }
+}
+
+/// On Python 2, file system paths are represented as byte strings.
+#[cfg(feature="python27-sys")]
+fn fs_decode(py: Python, path: &[u8]) -> PyString {
+    PyBytes::new(py, path).into_basestring()
+}
+
+/// Converts `OsStr` to a Python `str`.
+///
+/// On Unix, the bytes of the path are decoded using the file system encoding with the
+/// `surrogateescape` error handler, so that non-UTF-8 paths round-trip through Python.
+/// On Python 2, the byte string is used as-is.
+impl ToPyObject for OsStr {
+    type ObjectType = PyString;
+
+    #[cfg(unix)]
+    fn to_py_object(&self, py: Python) -> PyString {
+        fs_decode(py, self.as_bytes())
+    }
+
+    #[cfg(not(unix))]
+    fn to_py_object(&self, py: Python) -> PyString {
+        PyString::new(py, &self.to_string_lossy())
+    }
+}
+
+/// Converts `OsString` to a Python `str`; see `OsStr` for details.
+impl ToPyObject for OsString {
+    type ObjectType = PyString;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyString {
+        self.as_os_str().to_py_object(py)
+    }
+}
+
+/// Converts `Path` to a Python `str`; see `OsStr` for details.
+/// Use `PathlibPath` to create a `pathlib.Path` object instead.
+impl ToPyObject for Path {
+    type ObjectType = PyString;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyString {
+        self.as_os_str().to_py_object(py)
+    }
+}
+
+/// Converts `PathBuf` to a Python `str`; see `OsStr` for details.
+/// Use `PathlibPath` to create a `pathlib.Path` object instead.
+impl ToPyObject for PathBuf {
+    type ObjectType = PyString;
+
+    #[inline]
+    fn to_py_object(&self, py: Python) -> PyString {
+        self.as_os_str().to_py_object(py)
+    }
+}
+
+/// Allows extracting file system paths from Python objects.
+///
+/// Accepts `str`, `bytes` and `os.PathLike` objects (like `pathlib.Path`).
+/// On Unix, `str` paths are encoded using the file system encoding with the
+/// `surrogateescape` error handler, so that non-UTF-8 paths are preserved.
+impl <'source> FromPyObject<'source> for OsString {
+    #[cfg(unix)]
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        let bytes = try!(fs_encode(py, obj));
+        Ok(OsString::from_vec(bytes.data(py).to_vec()))
+    }
+
+    #[cfg(not(unix))]
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        let bytes = try!(fs_encode(py, obj));
+        let path = fs_decode(py, bytes.data(py));
+        Ok(OsString::from(try!(path.to_string(py)).into_owned()))
+    }
+}
+
+/// Allows extracting file system paths from Python objects; see `OsString` for details.
+impl <'source> FromPyObject<'source> for PathBuf {
+    #[inline]
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        obj.extract::<OsString>(py).map(PathBuf::from)
+    }
+}
+
+/// Wrapper that converts a path to a `pathlib.Path` object
+/// instead of a `str`.
+///
+/// This type is only available on Python 3.
+///
+/// # Example
+/// ```
+/// use std::path::Path;
+/// use cpython::{Python, PathlibPath, ToPyObject, ObjectProtocol};
+///
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let path = PathlibPath(Path::new("/tmp")).to_py_object(py);
+/// assert_eq!("tmp", path.getattr(py, "name").unwrap().extract::<String>(py).unwrap());
+/// ```
+#[cfg(feature="python3-sys")]
+pub struct PathlibPath<P>(pub P);
+
+#[cfg(feature="python3-sys")]
+impl <P> ToPyObject for PathlibPath<P> where P: AsRef<Path> {
+    type ObjectType = PyObject;
+
+    fn to_py_object(&self, py: Python) -> PyObject {
+        let path = self.0.as_ref().to_py_object(py);
+        py.import("pathlib")
+            .and_then(|pathlib| pathlib.call(py, "Path", (path,), None))
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::OsString;
+    use std::path::{Path, PathBuf};
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objects::PyObject;
+
+    #[test]
+    fn test_extract() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("'/tmp/file.txt'", None, None).unwrap();
+        assert_eq!(Path::new("/tmp/file.txt"), obj.extract::<PathBuf>(py).unwrap());
+        let obj = py.eval("b'relative/dir'", None, None).unwrap();
+        assert_eq!(OsString::from("relative/dir"), obj.extract::<OsString>(py).unwrap());
+        assert!(py.eval("42", None, None).unwrap().extract::<PathBuf>(py).is_err());
+    }
+
+    #[test]
+    fn test_extract_path_like() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("type('PathLike', (object,), {'__fspath__': lambda self: 'from/fspath'})()",
+                          None, None).unwrap();
+        assert_eq!(Path::new("from/fspath"), obj.extract::<PathBuf>(py).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature="python3-sys")]
+    fn test_pathlib() {
+        use conversion::ToPyObject;
+        use objects::PathlibPath;
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let path = PathlibPath(Path::new("a/b")).to_py_object(py);
+        assert_eq!(Path::new("a/b"), path.extract::<PathBuf>(py).unwrap());
+        assert_eq!("a/b", path.to_string());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_non_utf8_roundtrip() {
+        use std::os::unix::ffi::{OsStrExt, OsStringExt};
+        use objectprotocol::ObjectProtocol;
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let path = OsString::from_vec(b"caf\xe9".to_vec());
+        let obj: PyObject = path.to_py_object(py).into_object();
+        assert_eq!(path, obj.extract::<OsString>(py).unwrap());
+        assert_eq!(b"caf\xe9", path.as_os_str().as_bytes());
+        #[cfg(feature="python3-sys")]
+        assert_eq!("'caf\\udce9'", obj.repr(py).unwrap().to_string_lossy(py));
+    }
+}
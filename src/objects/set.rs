@@ -0,0 +1,337 @@
+// Copyright (c) 2017 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::{ptr, collections, hash, cmp};
+use ffi;
+use python::{Python, PythonObject, PythonObjectDowncastError};
+use conversion::{ToPyObject, FromPyObject};
+use objects::{PyObject, PyList, PyIterator};
+use objectprotocol::ObjectProtocol;
+use err::{self, PyErr, PyResult};
+
+/// Represents a Python `set`.
+pub struct PySet(PyObject);
+
+pyobject_newtype!(PySet, PySet_Check, PySet_Type);
+
+/// Represents a Python `frozenset`.
+pub struct PyFrozenSet(PyObject);
+
+pyobject_newtype!(PyFrozenSet, PyFrozenSet_Check, PyFrozenSet_Type);
+
+impl PySet {
+    /// Creates a new empty set.
+    ///
+    /// May panic when running out of memory.
+    pub fn empty(py: Python) -> PySet {
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(py, ffi::PySet_New(ptr::null_mut()))
+        }
+    }
+
+    /// Creates a new set containing the given elements.
+    ///
+    /// Fails if one of the elements is not hashable.
+    pub fn new(py: Python, elements: &[PyObject]) -> PyResult<PySet> {
+        let list = PyList::new(py, elements);
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PySet_New(list.as_object().as_ptr()))
+        }
+    }
+
+    /// Removes all elements from the set.
+    #[inline]
+    pub fn clear(&self, _py: Python) {
+        unsafe { ffi::PySet_Clear(self.0.as_ptr()); }
+    }
+
+    /// Return the number of items in the set.
+    /// This is equivalent to len(p) on a set.
+    #[inline]
+    pub fn len(&self, _py: Python) -> usize {
+        unsafe { ffi::PySet_Size(self.0.as_ptr()) as usize }
+    }
+
+    /// Determine if the set contains the specified key.
+    /// This is equivalent to the Python expression `key in self`.
+    pub fn contains<K>(&self, py: Python, key: K) -> PyResult<bool> where K: ToPyObject {
+        contains(py, &self.0, key)
+    }
+
+    /// Adds an element to the set.
+    /// This is equivalent to the Python expression `self.add(key)`.
+    pub fn add<K>(&self, py: Python, key: K) -> PyResult<()> where K: ToPyObject {
+        key.with_borrowed_ptr(py, |key| unsafe {
+            err::error_on_minusone(py, ffi::PySet_Add(self.0.as_ptr(), key))
+        })
+    }
+
+    /// Removes an element from the set if it is present.
+    /// Returns whether the element was found.
+    /// This is equivalent to the Python expression `self.discard(key)`.
+    pub fn discard<K>(&self, py: Python, key: K) -> PyResult<bool> where K: ToPyObject {
+        key.with_borrowed_ptr(py, |key| unsafe {
+            match ffi::PySet_Discard(self.0.as_ptr(), key) {
+                1 => Ok(true),
+                0 => Ok(false),
+                _ => Err(PyErr::fetch(py))
+            }
+        })
+    }
+
+    /// Removes and returns an arbitrary element from the set.
+    /// Returns `None` if the set is empty.
+    pub fn pop(&self, py: Python) -> Option<PyObject> {
+        let element = unsafe {
+            PyObject::from_owned_ptr_opt(py, ffi::PySet_Pop(self.0.as_ptr()))
+        };
+        if element.is_none() {
+            // clear the KeyError raised for empty sets
+            PyErr::fetch(py);
+        }
+        element
+    }
+
+    /// Returns an iterator over the elements of the set.
+    ///
+    /// Modifying the set during the iteration causes the iterator to return a `RuntimeError`.
+    pub fn iter<'p>(&self, py: Python<'p>) -> PyResult<PyIterator<'p>> {
+        self.0.iter(py)
+    }
+}
+
+impl PyFrozenSet {
+    /// Creates a new frozenset containing the given elements.
+    ///
+    /// Fails if one of the elements is not hashable.
+    pub fn new(py: Python, elements: &[PyObject]) -> PyResult<PyFrozenSet> {
+        let list = PyList::new(py, elements);
+        unsafe {
+            err::result_cast_from_owned_ptr(py, ffi::PyFrozenSet_New(list.as_object().as_ptr()))
+        }
+    }
+
+    /// Return the number of items in the frozenset.
+    /// This is equivalent to len(p) on a frozenset.
+    #[inline]
+    pub fn len(&self, _py: Python) -> usize {
+        unsafe { ffi::PySet_Size(self.0.as_ptr()) as usize }
+    }
+
+    /// Determine if the frozenset contains the specified key.
+    /// This is equivalent to the Python expression `key in self`.
+    pub fn contains<K>(&self, py: Python, key: K) -> PyResult<bool> where K: ToPyObject {
+        contains(py, &self.0, key)
+    }
+
+    /// Returns an iterator over the elements of the frozenset.
+    pub fn iter<'p>(&self, py: Python<'p>) -> PyResult<PyIterator<'p>> {
+        self.0.iter(py)
+    }
+}
+
+fn contains<K>(py: Python, set: &PyObject, key: K) -> PyResult<bool> where K: ToPyObject {
+    key.with_borrowed_ptr(py, |key| unsafe {
+        match ffi::PySet_Contains(set.as_ptr(), key) {
+            1 => Ok(true),
+            0 => Ok(false),
+            _ => Err(PyErr::fetch(py))
+        }
+    })
+}
+
+impl <T> ToPyObject for collections::HashSet<T>
+    where T: hash::Hash+cmp::Eq+ToPyObject
+{
+    type ObjectType = PySet;
+
+    fn to_py_object(&self, py: Python) -> PySet {
+        let set = PySet::empty(py);
+        for key in self {
+            set.add(py, key).unwrap();
+        }
+        set
+    }
+}
+
+impl <T> ToPyObject for collections::BTreeSet<T>
+    where T: cmp::Eq+ToPyObject
+{
+    type ObjectType = PySet;
+
+    fn to_py_object(&self, py: Python) -> PySet {
+        let set = PySet::empty(py);
+        for key in self {
+            set.add(py, key).unwrap();
+        }
+        set
+    }
+}
+
+/// Iterates over the elements of a `set` or `frozenset`.
+fn extract_set<'p>(py: Python<'p>, obj: &PyObject) -> PyResult<PyIterator<'p>>
+{
+    if unsafe { ffi::PyAnySet_Check(obj.as_ptr()) } == 0 {
+        return Err(PyErr::from(PythonObjectDowncastError(py)));
+    }
+    obj.iter(py)
+}
+
+impl <'source, T> FromPyObject<'source> for collections::HashSet<T>
+    where for<'a> T: FromPyObject<'a> + hash::Hash + cmp::Eq
+{
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        let mut set = collections::HashSet::new();
+        for item in try!(extract_set(py, obj)) {
+            let item = try!(item);
+            set.insert(try!(item.extract::<T>(py)));
+        }
+        Ok(set)
+    }
+}
+
+impl <'source, T> FromPyObject<'source> for collections::BTreeSet<T>
+    where for<'a> T: FromPyObject<'a> + cmp::Ord
+{
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<Self> {
+        let mut set = collections::BTreeSet::new();
+        for item in try!(extract_set(py, obj)) {
+            let item = try!(item);
+            set.insert(try!(item.extract::<T>(py)));
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashSet, BTreeSet};
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objects::{PySet, PyFrozenSet, PyList};
+
+    #[test]
+    fn test_set_new() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let set = PySet::new(py, &[1i32.to_py_object(py).into_object()]).unwrap();
+        assert_eq!(1, set.len(py));
+
+        let list = PyList::new(py, &[]).into_object();
+        assert!(PySet::new(py, &[list]).is_err());
+    }
+
+    #[test]
+    fn test_set_len() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = HashSet::new();
+        let set = v.to_py_object(py);
+        assert_eq!(0, set.len(py));
+        v.insert(7);
+        let set2 = v.to_py_object(py);
+        assert_eq!(1, set2.len(py));
+    }
+
+    #[test]
+    fn test_set_contains() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let set = PySet::new(py, &[1i32.to_py_object(py).into_object()]).unwrap();
+        assert!(set.contains(py, 1).unwrap());
+        assert!(!set.contains(py, 2).unwrap());
+        let list = PyList::new(py, &[]);
+        assert!(set.contains(py, list).is_err());
+    }
+
+    #[test]
+    fn test_set_add_discard() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let set = PySet::empty(py);
+        set.add(py, 1).unwrap();
+        set.add(py, 2).unwrap();
+        set.add(py, 1).unwrap();
+        assert_eq!(2, set.len(py));
+        assert!(set.discard(py, 1).unwrap());
+        assert!(!set.discard(py, 1).unwrap());
+        assert_eq!(1, set.len(py));
+        let list = PyList::new(py, &[]);
+        assert!(set.add(py, list).is_err());
+    }
+
+    #[test]
+    fn test_set_pop() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let set = PySet::new(py, &[1i32.to_py_object(py).into_object()]).unwrap();
+        assert_eq!(1, set.pop(py).unwrap().extract::<i32>(py).unwrap());
+        assert!(set.pop(py).is_none());
+        // pop() on an empty set must not leave an exception behind
+        assert!(!::err::PyErr::occurred(py));
+    }
+
+    #[test]
+    fn test_set_iter() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let set = PySet::new(py, &[1i32.to_py_object(py).into_object()]).unwrap();
+        let items: Vec<i32> = set.iter(py).unwrap().map(|e| e.unwrap().extract(py).unwrap()).collect();
+        assert_eq!(vec![1], items);
+    }
+
+    #[test]
+    fn test_frozenset() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let set = PyFrozenSet::new(py, &[1i32.to_py_object(py).into_object()]).unwrap();
+        assert_eq!(1, set.len(py));
+        assert!(set.contains(py, 1).unwrap());
+        assert!(!set.contains(py, 2).unwrap());
+        let items: Vec<i32> = set.iter(py).unwrap().map(|e| e.unwrap().extract(py).unwrap()).collect();
+        assert_eq!(vec![1], items);
+    }
+
+    #[test]
+    fn test_extract_hashset() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = HashSet::new();
+        v.insert(1);
+        v.insert(2);
+        let set = v.to_py_object(py).into_object();
+        assert_eq!(v, set.extract::<HashSet<i32>>(py).unwrap());
+        let frozenset = PyFrozenSet::new(py, &[1i32.to_py_object(py).into_object()]).unwrap().into_object();
+        assert_eq!(1, frozenset.extract::<HashSet<i32>>(py).unwrap().len());
+        let list = vec![1, 2].to_py_object(py).into_object();
+        assert!(list.extract::<HashSet<i32>>(py).is_err());
+    }
+
+    #[test]
+    fn test_extract_btreeset() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = BTreeSet::new();
+        v.insert("a".to_owned());
+        v.insert("b".to_owned());
+        let set = v.to_py_object(py).into_object();
+        assert_eq!(v, set.extract::<BTreeSet<String>>(py).unwrap());
+        let set = PySet::new(py, &[1i32.to_py_object(py).into_object()]).unwrap().into_object();
+        assert!(set.extract::<BTreeSet<String>>(py).is_err());
+    }
+}
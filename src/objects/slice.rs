@@ -0,0 +1,175 @@
+// Copyright (c) 2015 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use ffi;
+use python::{Python, PythonObject, PyClone, PythonObjectDowncastError};
+use conversion::{ToPyObject, FromPyObject};
+use err::{self, PyResult, PyErr};
+use super::object::PyObject;
+
+/// Represents a Python `slice` object, as created by the expression `seq[start:stop:step]`.
+pub struct PySlice(PyObject);
+
+pyobject_newtype!(PySlice, PySlice_Check, PySlice_Type);
+
+/// The normalized indices of a slice, as returned by
+/// [PySlice::indices](struct.PySlice.html#method.indices).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PySliceIndices {
+    pub start: isize,
+    pub stop: isize,
+    pub step: isize,
+    /// The number of elements selected by the slice.
+    pub slicelength: usize
+}
+
+impl PySlice {
+    /// Creates a new slice object.
+    /// `None` corresponds to an omitted value in the slice expression.
+    ///
+    /// This is equivalent to the python expression `slice(start, stop, step)`.
+    pub fn new(py: Python, start: Option<isize>, stop: Option<isize>, step: Option<isize>) -> PySlice {
+        let start = start.to_py_object(py);
+        let stop = stop.to_py_object(py);
+        let step = step.to_py_object(py);
+        unsafe {
+            err::cast_from_owned_ptr_or_panic(py,
+                ffi::PySlice_New(start.as_ptr(), stop.as_ptr(), step.as_ptr()))
+        }
+    }
+
+    /// Computes the start, stop and step indices selected by this slice
+    /// when applied to a sequence of the given length.
+    ///
+    /// Out-of-bounds indices are clipped like in Python's slicing;
+    /// negative indices count from the end of the sequence.
+    /// Fails with `ValueError` if the step is zero, and with `TypeError`
+    /// if the slice contains values that are not integers.
+    pub fn indices(&self, py: Python, length: usize) -> PyResult<PySliceIndices> {
+        let mut start = 0;
+        let mut stop = 0;
+        let mut step = 0;
+        let mut slicelength = 0;
+        unsafe {
+            try!(err::error_on_minusone(py, ffi::PySlice_GetIndicesEx(
+                self.0.as_ptr(), length as ffi::Py_ssize_t,
+                &mut start, &mut stop, &mut step, &mut slicelength)));
+        }
+        Ok(PySliceIndices {
+            start: start as isize,
+            stop: stop as isize,
+            step: step as isize,
+            slicelength: slicelength as usize
+        })
+    }
+}
+
+/// The key passed to `__getitem__`, `__setitem__` or `__delitem__` of a sequence:
+/// either a slice or an integer index.
+///
+/// Integer keys must implement `__index__`; floats and other non-integral
+/// numbers are rejected with a `TypeError`, just like for the built-in sequences.
+///
+/// # Example
+/// ```
+/// #[macro_use] extern crate cpython;
+/// use cpython::{Python, PyResult, PyErr, PyObject, PythonObject, SliceOrIndex, ToPyObject, exc};
+///
+/// py_class!(class Squares |py| {
+///     def __len__(&self) -> PyResult<usize> {
+///         Ok(10)
+///     }
+///     def __getitem__(&self, key: SliceOrIndex) -> PyResult<PyObject> {
+///         match key {
+///             SliceOrIndex::Index(i) if i >= 0 && i < 10 => Ok((i * i).to_py_object(py).into_object()),
+///             SliceOrIndex::Index(_) => Err(PyErr::new::<exc::IndexError, _>(py, "index out of range")),
+///             SliceOrIndex::Slice(slice) => {
+///                 let indices = try!(slice.indices(py, 10));
+///                 let squares: Vec<isize> = (0..indices.slicelength as isize)
+///                     .map(|i| indices.start + i * indices.step)
+///                     .map(|i| i * i)
+///                     .collect();
+///                 Ok(squares.to_py_object(py).into_object())
+///             }
+///         }
+///     }
+/// });
+/// # fn main() {}
+/// ```
+pub enum SliceOrIndex {
+    Slice(PySlice),
+    Index(isize)
+}
+
+impl <'source> FromPyObject<'source> for SliceOrIndex {
+    fn extract(py: Python, obj: &'source PyObject) -> PyResult<SliceOrIndex> {
+        if let Ok(slice) = obj.cast_as::<PySlice>(py) {
+            return Ok(SliceOrIndex::Slice(slice.clone_ref(py)));
+        }
+        unsafe {
+            if ffi::PyIndex_Check(obj.as_ptr()) == 0 {
+                return Err(PyErr::from(PythonObjectDowncastError(py)));
+            }
+            let index = ffi::PyNumber_AsSsize_t(obj.as_ptr(), ffi::PyExc_IndexError);
+            if index == -1 && PyErr::occurred(py) {
+                return Err(PyErr::fetch(py));
+            }
+            Ok(SliceOrIndex::Index(index as isize))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use objects::{PySlice, PySliceIndices, SliceOrIndex};
+
+    #[test]
+    fn test_indices() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let slice = PySlice::new(py, Some(1), None, Some(2));
+        assert_eq!(PySliceIndices { start: 1, stop: 10, step: 2, slicelength: 5 },
+                   slice.indices(py, 10).unwrap());
+
+        let slice = PySlice::new(py, None, Some(-3), Some(-1));
+        assert_eq!(PySliceIndices { start: 4, stop: 2, step: -1, slicelength: 2 },
+                   slice.indices(py, 5).unwrap());
+
+        let slice = PySlice::new(py, None, None, Some(0));
+        assert!(slice.indices(py, 5).is_err());
+    }
+
+    #[test]
+    fn test_extract() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("slice(None, 3)", None, None).unwrap();
+        match obj.extract::<SliceOrIndex>(py).unwrap() {
+            SliceOrIndex::Slice(slice) => assert_eq!(3, slice.indices(py, 10).unwrap().stop),
+            SliceOrIndex::Index(_) => panic!("expected slice")
+        }
+        match py.eval("-2", None, None).unwrap().extract::<SliceOrIndex>(py).unwrap() {
+            SliceOrIndex::Index(i) => assert_eq!(-2, i),
+            SliceOrIndex::Slice(_) => panic!("expected index")
+        }
+        assert!(py.eval("1.5", None, None).unwrap().extract::<SliceOrIndex>(py).is_err());
+        assert!(py.eval("'a'", None, None).unwrap().extract::<SliceOrIndex>(py).is_err());
+        assert!(PySlice::new(py, None, None, None).into_object().extract::<SliceOrIndex>(py).is_ok());
+    }
+}
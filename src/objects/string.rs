@@ -20,6 +20,8 @@ use std;
 use std::{mem, str, char};
 use std::ascii::AsciiExt;
 use std::borrow::Cow;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::ffi::CString;
 use libc::c_char;
 use ffi;
 use python::{Python, PythonObject, PyClone, ToPythonPointer, PythonObjectDowncastError};
@@ -202,6 +204,19 @@ impl <'a> PyStringData<'a> {
     }
 }
 
+/// Converts the name of an encoding or error handler into a C string.
+fn codec_arg(py: Python, name: &str) -> PyResult<CString> {
+    CString::new(name).map_err(|_| PyErr::new::<exc::ValueError, _>(py, "embedded null character"))
+}
+
+/// Encodes the unicode object `unicode` using the codec registered for `encoding`.
+unsafe fn encode_unicode(py: Python, unicode: *mut ffi::PyObject, encoding: &str, errors: &str) -> PyResult<PyBytes> {
+    let encoding = try!(codec_arg(py, encoding));
+    let errors = try!(codec_arg(py, errors));
+    err::result_cast_from_owned_ptr(py,
+        ffi::PyUnicode_AsEncodedString(unicode, encoding.as_ptr(), errors.as_ptr()))
+}
+
 impl PyString {
     /// Creates a new Python string object.
     ///
@@ -231,6 +246,40 @@ impl PyString {
         new_impl(py, s)
     }
 
+    /// Creates a new interned Python string object.
+    ///
+    /// Interned strings with the same contents are the same object,
+    /// which allows Python to compare attribute names by pointer.
+    /// On Python 2.7, only byte strings can be interned, so this function
+    /// returns a (non-interned) unicode string if the input is not ASCII-only.
+    ///
+    /// Use `py_intern!` to create the string only once per call site.
+    ///
+    /// Panics if out of memory.
+    pub fn intern(py: Python, s: &str) -> PyString {
+        #[cfg(feature="python27-sys")]
+        fn intern_impl(py: Python, s: &str) -> PyString {
+            let s = PyString::new(py, s);
+            if s.0.cast_as::<PyBytes>(py).is_err() {
+                return s;
+            }
+            unsafe {
+                let mut ptr = s.0.steal_ptr();
+                ffi::PyString_InternInPlace(&mut ptr);
+                err::cast_from_owned_ptr_or_panic(py, ptr)
+            }
+        }
+        #[cfg(feature="python3-sys")]
+        fn intern_impl(py: Python, s: &str) -> PyString {
+            unsafe {
+                let mut ptr = PyString::new(py, s).0.steal_ptr();
+                ffi::PyUnicode_InternInPlace(&mut ptr);
+                err::cast_from_owned_ptr_or_panic(py, ptr)
+            }
+        }
+        intern_impl(py, s)
+    }
+
     /// Gets the python string data in its underlying representation.
     ///
     /// For Python 2 byte strings, this function always returns `PyStringData::Utf8`,
@@ -268,16 +317,32 @@ impl PyString {
 
     /// Convert the `PyString` into a Rust string.
     ///
-    /// On Python 2.7, if the `PyString` refers to a byte string,
-    /// it will be decoded using UTF-8.
+    /// If the `PyString` refers to a byte string, it will be decoded using UTF-8.
     ///
     /// Returns a `UnicodeDecodeError` if the input is not valid unicode
-    /// (containing unpaired surrogates, or a Python 2.7 byte string that is
-    /// not valid UTF-8).
+    /// (containing unpaired surrogates, or a byte string that is not valid UTF-8).
+    #[cfg(feature="python27-sys")]
     pub fn to_string(&self, py: Python) -> PyResult<Cow<str>> {
         self.data(py).to_string(py)
     }
 
+    /// Convert the `PyString` into a Rust string.
+    ///
+    /// Returns a `UnicodeEncodeError` if the input is not valid unicode
+    /// (containing unpaired surrogates).
+    #[cfg(feature="python3-sys")]
+    pub fn to_string(&self, py: Python) -> PyResult<Cow<str>> {
+        unsafe {
+            let mut size : ffi::Py_ssize_t = 0;
+            let data = ffi::PyUnicode_AsUTF8AndSize(self.as_ptr(), &mut size) as *const u8;
+            if data.is_null() {
+                return Err(PyErr::fetch(py));
+            }
+            let bytes = std::slice::from_raw_parts(data, size as usize);
+            Ok(Cow::Borrowed(std::str::from_utf8_unchecked(bytes)))
+        }
+    }
+
     /// Convert the `PyString` into a Rust string.
     ///
     /// On Python 2.7, if the `PyString` refers to a byte string,
@@ -288,6 +353,30 @@ impl PyString {
     pub fn to_string_lossy(&self, py: Python) -> Cow<str> {
         self.data(py).to_string_lossy()
     }
+
+    /// Encodes the string using the codec registered for `encoding`.
+    ///
+    /// `errors` names the error handler, e.g. `"strict"`, `"replace"` or `"ignore"`.
+    /// With `"strict"`, characters that cannot be encoded result in a `UnicodeEncodeError`.
+    ///
+    /// On Python 2.7, if the `PyString` refers to a byte string,
+    /// it will be decoded using UTF-8 first.
+    pub fn encode(&self, py: Python, encoding: &str, errors: &str) -> PyResult<PyBytes> {
+        self.encode_impl(py, encoding, errors)
+    }
+
+    #[cfg(feature="python27-sys")]
+    fn encode_impl(&self, py: Python, encoding: &str, errors: &str) -> PyResult<PyBytes> {
+        match self.0.cast_as::<PyBytes>(py) {
+            Ok(bytes) => try!(bytes.decode(py, "utf-8", errors)).encode(py, encoding, errors),
+            Err(_) => unsafe { encode_unicode(py, self.as_ptr(), encoding, errors) }
+        }
+    }
+
+    #[cfg(feature="python3-sys")]
+    fn encode_impl(&self, py: Python, encoding: &str, errors: &str) -> PyResult<PyBytes> {
+        unsafe { encode_unicode(py, self.as_ptr(), encoding, errors) }
+    }
 }
 
 impl PyBytes {
@@ -313,6 +402,21 @@ impl PyBytes {
         }
     }
 
+    /// Decodes the byte string using the codec registered for `encoding`.
+    ///
+    /// `errors` names the error handler, e.g. `"strict"`, `"replace"` or `"ignore"`.
+    /// With `"strict"`, invalid input results in a `UnicodeDecodeError`.
+    pub fn decode(&self, py: Python, encoding: &str, errors: &str) -> PyResult<PyUnicode> {
+        let data = self.data(py);
+        let encoding = try!(codec_arg(py, encoding));
+        let errors = try!(codec_arg(py, errors));
+        unsafe {
+            err::result_cast_from_owned_ptr(py,
+                ffi::PyUnicode_Decode(data.as_ptr() as *const c_char, data.len() as ffi::Py_ssize_t,
+                    encoding.as_ptr(), errors.as_ptr()))
+        }
+    }
+
     /// Converts from `PyBytes` to `PyString`.
     /// This method is only available on Python 2.
     #[cfg(feature="python27-sys")]
@@ -383,6 +487,109 @@ impl PyUnicode {
     pub fn to_string_lossy(&self, py: Python) -> Cow<str> {
         self.data(py).to_string_lossy()
     }
+
+    /// Encodes the string using the codec registered for `encoding`.
+    ///
+    /// `errors` names the error handler, e.g. `"strict"`, `"replace"` or `"ignore"`.
+    /// With `"strict"`, characters that cannot be encoded result in a `UnicodeEncodeError`.
+    pub fn encode(&self, py: Python, encoding: &str, errors: &str) -> PyResult<PyBytes> {
+        unsafe { encode_unicode(py, self.as_ptr(), encoding, errors) }
+    }
+}
+
+/// An interned Python string that is created on first use and then kept alive
+/// for the remainder of the program.
+///
+/// With the `nightly` feature, `InternedString::new` is a `const fn`, so this type
+/// can be used in statics; otherwise, use the `py_intern!` macro.
+///
+/// # Example
+/// ```
+/// use cpython::{Python, InternedString, ObjectProtocol};
+///
+/// let real = InternedString::new("real");
+///
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let obj = py.eval("1.5", None, None).unwrap();
+/// let value: f64 = obj.getattr(py, real.get(py)).unwrap().extract(py).unwrap();
+/// assert_eq!(value, 1.5);
+/// ```
+pub struct InternedString {
+    text: &'static str,
+    ptr: AtomicUsize
+}
+
+impl InternedString {
+    /// Creates a new `InternedString`. The Python string is not created until the first call to `get()`.
+    #[inline]
+    #[cfg(feature="nightly")]
+    pub const fn new(text: &'static str) -> InternedString {
+        InternedString { text: text, ptr: AtomicUsize::new(0) }
+    }
+
+    /// Creates a new `InternedString`. The Python string is not created until the first call to `get()`.
+    #[inline]
+    #[cfg(not(feature="nightly"))]
+    pub fn new(text: &'static str) -> InternedString {
+        InternedString { text: text, ptr: AtomicUsize::new(0) }
+    }
+
+    /// Gets the interned Python string, creating it if necessary.
+    pub fn get<'a>(&'a self, py: Python<'a>) -> &'a PyString {
+        get_interned(py, &self.ptr, self.text)
+    }
+}
+
+/// Gets the interned Python string stored in `cell`, creating it if `cell` is still zero.
+///
+/// `cell` holds the `*mut ffi::PyObject` of the string; the GIL protects its initialization.
+#[doc(hidden)] // used in py_intern!() macro
+pub fn get_interned<'a>(py: Python<'a>, cell: &'a AtomicUsize, text: &str) -> &'a PyString {
+    if cell.load(Ordering::Relaxed) == 0 {
+        cell.store(PyString::intern(py, text).steal_ptr(py) as usize, Ordering::Relaxed);
+    }
+    unsafe {
+        // `AtomicUsize` has the same in-memory representation as `usize`,
+        // and the cell is not modified after initialization.
+        let ptr = &*(cell as *const AtomicUsize as *const *mut ffi::PyObject);
+        PyObject::borrow_from_ptr(ptr).unchecked_cast_as()
+    }
+}
+
+/// Returns an interned `&PyString` for a string literal.
+///
+/// The Python string is created on the first use of each `py_intern!` invocation,
+/// and reused afterwards. Pass it to `getattr`, `setattr`, `call_method` etc.
+/// to avoid converting the name on every call.
+///
+/// Syntax: `py_intern!(py, "name")`
+///
+/// # Example
+/// ```
+/// #[macro_use] extern crate cpython;
+/// use cpython::{Python, PyResult, PyObject, ObjectProtocol, NoArgs};
+///
+/// fn upper_all(py: Python, items: &[PyObject]) -> PyResult<Vec<String>> {
+///     items.iter().map(|item| {
+///         item.call_method(py, py_intern!(py, "upper"), NoArgs, None)?.extract(py)
+///     }).collect()
+/// }
+///
+/// # fn main() {
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let items = vec![py.eval("'a'", None, None).unwrap(), py.eval("'b'", None, None).unwrap()];
+/// assert_eq!(upper_all(py, &items).unwrap(), vec!["A", "B"]);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! py_intern {
+    ($py:expr, $text:expr) => {{
+        #[allow(deprecated)]
+        static INTERNED: ::std::sync::atomic::AtomicUsize = ::std::sync::atomic::ATOMIC_USIZE_INIT;
+        $crate::_detail::get_interned($py, &INTERNED, $text)
+    }}
 }
 
 /// Converts Rust `str` to Python object.
@@ -449,6 +656,8 @@ impl RefFromPyObject for str {
 mod test {
     use python::{Python, PythonObject};
     use conversion::{ToPyObject, RefFromPyObject};
+    use objectprotocol::ObjectProtocol;
+    use objects::{exc, NoArgs, PyBytes, PyString, PyUnicode, InternedString};
 
     #[test]
     fn test_non_bmp() {
@@ -473,5 +682,87 @@ mod test {
             }).unwrap();
         assert!(called);
     }
+
+    #[test]
+    fn test_encode_decode() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyUnicode::new(py, "caf\u{e9}");
+        let bytes = s.encode(py, "latin-1", "strict").unwrap();
+        assert_eq!(bytes.data(py), b"caf\xe9");
+        assert_eq!(bytes.decode(py, "latin-1", "strict").unwrap().to_string(py).unwrap(), "caf\u{e9}");
+        assert_eq!(bytes.decode(py, "cp1252", "strict").unwrap().to_string(py).unwrap(), "caf\u{e9}");
+
+        let sjis = PyBytes::new(py, b"\x93\xfa\x96\x7b");
+        assert_eq!(sjis.decode(py, "shift_jis", "strict").unwrap().to_string(py).unwrap(), "\u{65e5}\u{672c}");
+    }
+
+    #[test]
+    fn test_codec_errors() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let bytes = PyBytes::new(py, b"caf\xe9");
+        let err = bytes.decode(py, "utf-8", "strict").err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::UnicodeDecodeError>()));
+        assert_eq!(bytes.decode(py, "utf-8", "replace").unwrap().to_string(py).unwrap(), "caf\u{fffd}");
+
+        let s = PyUnicode::new(py, "\u{20ac}");
+        let err = s.encode(py, "latin-1", "strict").err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::UnicodeEncodeError>()));
+        assert_eq!(s.encode(py, "latin-1", "ignore").unwrap().data(py), b"");
+
+        let err = bytes.decode(py, "no-such-codec", "strict").err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::LookupError>()));
+        let err = bytes.decode(py, "utf\0-8", "strict").err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::ValueError>()));
+    }
+
+    #[test]
+    fn test_intern() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let a = PyString::intern(py, "some_attribute_name");
+        let b = PyString::intern(py, "some_attribute_name");
+        assert_eq!(a.as_object(), b.as_object());
+
+        fn name(py: Python) -> &PyString {
+            py_intern!(py, "another_attribute_name")
+        }
+        assert_eq!(name(py).as_object(), name(py).as_object());
+        assert_eq!(name(py).to_string(py).unwrap(), "another_attribute_name");
+        assert_eq!(name(py).as_object(), PyString::intern(py, "another_attribute_name").as_object());
+
+        let interned = InternedString::new("another_attribute_name");
+        assert_eq!(interned.get(py).as_object(), name(py).as_object());
+    }
+
+    #[test]
+    fn test_intern_getattr() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("1.5", None, None).unwrap();
+        let real: f64 = obj.getattr(py, py_intern!(py, "real")).unwrap().extract(py).unwrap();
+        assert_eq!(real, 1.5);
+        let hex: String = obj.call_method(py, py_intern!(py, "hex"), NoArgs, None).unwrap().extract(py).unwrap();
+        assert_eq!(hex, "0x1.8000000000000p+0");
+    }
+
+    #[test]
+    fn test_encode_basestring() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let s = PyString::new(py, "abc");
+        assert_eq!(s.encode(py, "utf-16-le", "strict").unwrap().data(py), b"a\0b\0c\0");
+    }
+
+    #[cfg(feature="python3-sys")]
+    #[test]
+    fn test_to_string_unpaired_surrogate() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = py.eval("'\\ud800'", None, None).unwrap();
+        let err = obj.cast_as::<PyString>(py).unwrap().to_string(py).err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::UnicodeEncodeError>()));
+    }
 }
 
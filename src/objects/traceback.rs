@@ -0,0 +1,135 @@
+// Copyright (c) 2018 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use python::{Python, PythonObject, PyClone};
+use objectprotocol::ObjectProtocol;
+use err::PyResult;
+use super::object::PyObject;
+
+/// Represents a Python traceback object, as stored in `PyErr::ptraceback`.
+///
+/// Each traceback object describes one stack frame;
+/// the entries are linked from the outermost to the innermost frame.
+pub struct PyTraceback(PyObject);
+
+pyobject_newtype!(PyTraceback, PyTraceBack_Check, PyTraceBack_Type);
+
+/// A stack frame entry of a traceback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PyTracebackFrame {
+    /// The name of the source file.
+    pub filename: String,
+    /// The line number that was being executed.
+    pub line_number: u32,
+    /// The name of the function (or `<module>` for module-level code).
+    pub function_name: String
+}
+
+impl PyTraceback {
+    /// Returns the stack frame entry described by this traceback object.
+    pub fn frame(&self, py: Python) -> PyResult<PyTracebackFrame> {
+        let code = try!(try!(self.0.getattr(py, "tb_frame")).getattr(py, "f_code"));
+        Ok(PyTracebackFrame {
+            filename: try!(try!(code.getattr(py, "co_filename")).extract(py)),
+            line_number: try!(try!(self.0.getattr(py, "tb_lineno")).extract(py)),
+            function_name: try!(try!(code.getattr(py, "co_name")).extract(py))
+        })
+    }
+
+    /// Returns the traceback object for the next (inner) stack frame,
+    /// or `None` if this is the innermost frame.
+    pub fn next(&self, py: Python) -> PyResult<Option<PyTraceback>> {
+        let next = try!(self.0.getattr(py, "tb_next"));
+        if next == py.None() {
+            Ok(None)
+        } else {
+            Ok(Some(try!(next.cast_into(py))))
+        }
+    }
+
+    /// Returns an iterator over the stack frame entries,
+    /// from the outermost to the innermost frame.
+    pub fn frames<'p>(&self, py: Python<'p>) -> PyTracebackFrames<'p> {
+        PyTracebackFrames { py: py, next: Some(self.clone_ref(py)) }
+    }
+}
+
+/// Iterator over the stack frame entries of a traceback. Created by `PyTraceback::frames()`.
+pub struct PyTracebackFrames<'p> {
+    py: Python<'p>,
+    next: Option<PyTraceback>
+}
+
+impl <'p> Iterator for PyTracebackFrames<'p> {
+    type Item = PyResult<PyTracebackFrame>;
+
+    /// Returns the next stack frame entry.
+    /// If an exception occurs, returns `Some(Err(..))` and stops the iteration.
+    fn next(&mut self) -> Option<PyResult<PyTracebackFrame>> {
+        let py = self.py;
+        let tb = match self.next.take() {
+            Some(tb) => tb,
+            None => return None
+        };
+        let frame = tb.frame(py);
+        if frame.is_ok() {
+            match tb.next(py) {
+                Ok(next) => self.next = next,
+                Err(e) => return Some(Err(e))
+            }
+        }
+        Some(frame)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::Python;
+    use objects::exc;
+    use super::PyTracebackFrame;
+
+    #[test]
+    fn test_frames() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let err = py.run("def f():\n    raise ValueError('boom')\nf()\n", None, None).unwrap_err();
+        let tb = err.traceback(py).unwrap();
+        let frames: Vec<PyTracebackFrame> = tb.frames(py).collect::<Result<_, _>>().unwrap();
+        assert_eq!(frames, vec![
+            PyTracebackFrame { filename: "<string>".to_owned(), line_number: 3, function_name: "<module>".to_owned() },
+            PyTracebackFrame { filename: "<string>".to_owned(), line_number: 2, function_name: "f".to_owned() },
+        ]);
+        assert!(tb.next(py).unwrap().unwrap().next(py).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_format_traceback() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let err = py.run("def f():\n    raise ValueError('boom')\nf()\n", None, None).unwrap_err();
+        assert_eq!(err.format_traceback(py).unwrap(),
+            "Traceback (most recent call last):\n  \
+             File \"<string>\", line 3, in <module>\n  \
+             File \"<string>\", line 2, in f\n\
+             ValueError: boom\n");
+
+        let err = ::PyErr::new::<exc::KeyError, _>(py, "key");
+        assert!(err.traceback(py).is_none());
+        assert_eq!(err.format_traceback(py).unwrap(), "KeyError: 'key'\n");
+    }
+}
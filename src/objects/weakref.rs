@@ -0,0 +1,203 @@
+// Copyright (c) 2018 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::ptr;
+use ffi;
+use python::{Python, PythonObject, PythonObjectWithCheckedDowncast, ToPythonPointer};
+use err::{self, PyResult};
+use super::object::PyObject;
+use super::PyCFunction;
+
+/// Represents a Python weak reference (`weakref.ref`).
+///
+/// A weak reference does not keep the referenced object alive;
+/// use `upgrade()` to get a strong reference to the object if it still exists.
+pub struct PyWeakRef(PyObject);
+
+pyobject_newtype!(PyWeakRef, PyWeakref_CheckRef);
+
+/// Represents a Python weak reference proxy (`weakref.proxy`).
+///
+/// The proxy forwards attribute access and calls to the referenced object,
+/// and raises `ReferenceError` once the object no longer exists.
+pub struct PyWeakProxy(PyObject);
+
+pyobject_newtype!(PyWeakProxy, PyWeakref_CheckProxy);
+
+unsafe fn new_weakref<T>(py: Python, obj: &PyObject, callback: Option<&PyObject>,
+    new_fn: unsafe extern "C" fn(*mut ffi::PyObject, *mut ffi::PyObject) -> *mut ffi::PyObject
+) -> PyResult<T>
+    where T: PythonObjectWithCheckedDowncast
+{
+    let callback = match callback {
+        Some(callback) => callback.as_ptr(),
+        None => ptr::null_mut()
+    };
+    err::result_cast_from_owned_ptr(py, new_fn(obj.as_ptr(), callback))
+}
+
+/// Wraps a Rust closure into a Python function that can be used as weak reference callback.
+fn closure_callback<T, F>(py: Python, f: F) -> PyCFunction
+    where T: PythonObjectWithCheckedDowncast, F: Fn(Python, T) + Send + 'static
+{
+    PyCFunction::from_closure(py, "weakref_callback", move |py, args, _| {
+        let weakref = try!(args.get_item(py, 0).cast_into::<T>(py));
+        f(py, weakref);
+        Ok(py.None())
+    })
+}
+
+/// Returns the object referenced by a weak reference or proxy, or `None` if it was destroyed.
+fn referent(py: Python, weakref: &PyObject) -> Option<PyObject> {
+    unsafe {
+        let p = ffi::PyWeakref_GetObject(weakref.as_ptr());
+        if p == ffi::Py_None() {
+            None
+        } else {
+            Some(PyObject::from_borrowed_ptr(py, p))
+        }
+    }
+}
+
+impl PyWeakRef {
+    /// Creates a new weak reference to `obj`.
+    ///
+    /// If `callback` is given, it is called with the weak reference as argument
+    /// when `obj` is about to be destroyed.
+    /// Raises `TypeError` if `obj` does not support weak references.
+    pub fn new(py: Python, obj: &PyObject, callback: Option<&PyObject>) -> PyResult<PyWeakRef> {
+        unsafe { new_weakref(py, obj, callback, ffi::PyWeakref_NewRef) }
+    }
+
+    /// Creates a new weak reference to `obj`, calling the Rust closure `f`
+    /// when `obj` is about to be destroyed.
+    ///
+    /// The closure receives the (now dead) weak reference; this can be used
+    /// to remove entries from a cache keyed on weak references.
+    /// If the closure panics, the panic is reported as an unraisable Python exception.
+    pub fn with_callback<F>(py: Python, obj: &PyObject, f: F) -> PyResult<PyWeakRef>
+        where F: Fn(Python, PyWeakRef) + Send + 'static
+    {
+        let callback = closure_callback(py, f);
+        PyWeakRef::new(py, obj, Some(callback.as_object()))
+    }
+
+    /// Returns a strong reference to the referenced object,
+    /// or `None` if the object was already destroyed.
+    #[inline]
+    pub fn upgrade(&self, py: Python) -> Option<PyObject> {
+        referent(py, &self.0)
+    }
+}
+
+impl PyWeakProxy {
+    /// Creates a new weak reference proxy for `obj`.
+    ///
+    /// If `callback` is given, it is called with the proxy as argument
+    /// when `obj` is about to be destroyed.
+    /// Raises `TypeError` if `obj` does not support weak references.
+    pub fn new(py: Python, obj: &PyObject, callback: Option<&PyObject>) -> PyResult<PyWeakProxy> {
+        unsafe { new_weakref(py, obj, callback, ffi::PyWeakref_NewProxy) }
+    }
+
+    /// Creates a new weak reference proxy for `obj`, calling the Rust closure `f`
+    /// when `obj` is about to be destroyed.
+    pub fn with_callback<F>(py: Python, obj: &PyObject, f: F) -> PyResult<PyWeakProxy>
+        where F: Fn(Python, PyWeakProxy) + Send + 'static
+    {
+        let callback = closure_callback(py, f);
+        PyWeakProxy::new(py, obj, Some(callback.as_object()))
+    }
+
+    /// Returns a strong reference to the referenced object,
+    /// or `None` if the object was already destroyed.
+    #[inline]
+    pub fn upgrade(&self, py: Python) -> Option<PyObject> {
+        referent(py, &self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use python::{Python, PythonObject};
+    use conversion::ToPyObject;
+    use objectprotocol::ObjectProtocol;
+    use objects::{PyObject, exc};
+    use super::{PyWeakRef, PyWeakProxy};
+
+    fn new_instance(py: Python) -> PyObject {
+        py.eval("type('C', (object,), {'value': 42})()", None, None).unwrap()
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = new_instance(py);
+        let weakref = PyWeakRef::new(py, &obj, None).unwrap();
+        assert!(weakref.upgrade(py).unwrap() == obj);
+        drop(obj);
+        assert!(weakref.upgrade(py).is_none());
+    }
+
+    #[test]
+    fn test_unsupported_type() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let err = PyWeakRef::new(py, &5i32.to_py_object(py).into_object(), None).err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>()));
+    }
+
+    #[test]
+    fn test_closure_callback() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let obj = new_instance(py);
+        let calls2 = calls.clone();
+        let weakref = PyWeakRef::with_callback(py, &obj, move |py, weakref| {
+            assert!(weakref.upgrade(py).is_none());
+            calls2.fetch_add(1, Ordering::SeqCst);
+        }).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        drop(obj);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(weakref.upgrade(py).is_none());
+    }
+
+    #[test]
+    fn test_proxy() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let obj = new_instance(py);
+        let calls2 = calls.clone();
+        let proxy = PyWeakProxy::with_callback(py, &obj, move |_, _| {
+            calls2.fetch_add(1, Ordering::SeqCst);
+        }).unwrap();
+        assert_eq!(proxy.as_object().getattr(py, "value").unwrap().extract::<i32>(py).unwrap(), 42);
+        assert!(proxy.upgrade(py).unwrap() == obj);
+        drop(obj);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(proxy.upgrade(py).is_none());
+        let err = proxy.as_object().getattr(py, "value").err().unwrap();
+        assert!(err.matches(py, py.get_type::<exc::ReferenceError>()));
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright (c) 2016 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::cell::RefCell;
+use python::{Python, PythonObject, PyClone};
+use conversion::ToPyObject;
+use objects::{exc, PyObject};
+use err::{PyErr, PyResult};
+
+/// Produces the next item of the wrapped Rust iterator, converted to a Python object.
+type NextFn = Box<FnMut(Python) -> Option<PyResult<PyObject>> + Send>;
+
+py_class!(pub class PyRustIterator |py| {
+    data next_item: RefCell<NextFn>;
+
+    def __iter__(&self) -> PyResult<PyRustIterator> {
+        Ok(self.clone_ref(py))
+    }
+
+    def __next__(&self) -> PyResult<Option<PyObject>> {
+        let mut next_item = match self.next_item(py).try_borrow_mut() {
+            Ok(next_item) => next_item,
+            Err(_) => return Err(PyErr::new::<exc::ValueError, _>(py, "iterator already executing"))
+        };
+        match (&mut *next_item)(py) {
+            Some(Ok(item)) => Ok(Some(item)),
+            Some(Err(e)) => Err(e),
+            None => Ok(None)
+        }
+    }
+});
+
+impl PyRustIterator {
+    /// Creates a Python iterator that yields the items of the Rust iterator `iter`.
+    ///
+    /// The items are converted to Python objects as they are requested.
+    /// If the Rust iterator panics, the Python iterator raises `SystemError`.
+    ///
+    /// # Example
+    /// ```
+    /// use cpython::{Python, PyRustIterator, ObjectProtocol};
+    ///
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// let squares = PyRustIterator::new(py, (1..4).map(|i| i * i)).unwrap();
+    /// let list: Vec<i32> = py.eval("list", None, None).unwrap()
+    ///     .call(py, (squares,), None).unwrap()
+    ///     .extract(py).unwrap();
+    /// assert_eq!(list, vec![1, 4, 9]);
+    /// ```
+    pub fn new<I, T>(py: Python, iter: I) -> PyResult<PyRustIterator>
+        where I: Iterator<Item=T> + Send + 'static, T: ToPyObject + 'static
+    {
+        PyRustIterator::from_results(py, iter.map(Ok))
+    }
+
+    /// Creates a Python iterator that yields the items of the Rust iterator `iter`.
+    ///
+    /// An `Err` item is raised as exception by the Python iterator;
+    /// the Rust iterator may continue to be used afterwards.
+    pub fn from_results<I, T>(py: Python, mut iter: I) -> PyResult<PyRustIterator>
+        where I: Iterator<Item=PyResult<T>> + Send + 'static, T: ToPyObject + 'static
+    {
+        let next_item: NextFn = Box::new(move |py| {
+            iter.next().map(|item| item.map(|value| value.into_py_object(py).into_object()))
+        });
+        PyRustIterator::create_instance(py, RefCell::new(next_item))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use python::{Python, PythonObject};
+    use objectprotocol::ObjectProtocol;
+    use objects::exc;
+    use err::{PyErr, PyResult};
+    use super::PyRustIterator;
+
+    #[test]
+    fn test_iterate() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let iter = PyRustIterator::new(py, vec!["a", "b"].into_iter()).unwrap().into_object();
+        let items: Vec<String> = iter.iter(py).unwrap().map(|item| item.unwrap().extract(py).unwrap()).collect();
+        assert_eq!(items, vec!["a", "b"]);
+        // exhausted iterators stay exhausted
+        assert_eq!(iter.iter(py).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_error() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let items: Vec<PyResult<i32>> = vec![Ok(1), Err(PyErr::new::<exc::KeyError, _>(py, "x")), Ok(3)];
+        let iter = PyRustIterator::from_results(py, items.into_iter()).unwrap().into_object();
+        let mut py_iter = iter.iter(py).unwrap();
+        assert_eq!(py_iter.next().unwrap().unwrap().extract::<i32>(py).unwrap(), 1);
+        assert!(py_iter.next().unwrap().unwrap_err().matches(py, py.get_type::<exc::KeyError>()));
+        assert_eq!(py_iter.next().unwrap().unwrap().extract::<i32>(py).unwrap(), 3);
+        assert!(py_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_panic() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let iter = PyRustIterator::new(py, (0..2).map(|i: i32| {
+            if i == 1 { panic!("boom"); }
+            i
+        })).unwrap().into_object();
+        let mut py_iter = iter.iter(py).unwrap();
+        assert_eq!(py_iter.next().unwrap().unwrap().extract::<i32>(py).unwrap(), 0);
+        assert!(py_iter.next().unwrap().unwrap_err().matches(py, py.get_type::<exc::SystemError>()));
+    }
+}
@@ -67,7 +67,7 @@ macro_rules! py_class_init_members {
 #[macro_export]
 #[doc(hidden)]
 macro_rules! py_class_instance_method {
-    ($py:ident, $class:ident :: $f:ident [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ]) => {{
+    ($py:ident, $class:ident :: $f:ident, $ret:tt [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ]) => {{
         unsafe extern "C" fn wrap_instance_method(
             slf: *mut $crate::_detail::ffi::PyObject,
             args: *mut $crate::_detail::ffi::PyObject,
@@ -88,8 +88,35 @@ macro_rules! py_class_instance_method {
                         })
                 })
         }
+        unsafe extern "C" fn wrap_instance_method_fast(
+            slf: *mut $crate::_detail::ffi::PyObject,
+            args: *mut *mut $crate::_detail::ffi::PyObject,
+            nargs: $crate::_detail::ffi::Py_ssize_t,
+            kwnames: *mut $crate::_detail::ffi::PyObject)
+        -> *mut $crate::_detail::ffi::PyObject
+        {
+            const LOCATION: &'static str = concat!(stringify!($class), ".", stringify!($f), "()");
+            $crate::_detail::handle_callback(
+                LOCATION, $crate::_detail::PyObjectCallbackConverter,
+                |py| {
+                    py_argparse_fastcall_raw!(py, Some(LOCATION), args, nargs, kwnames,
+                        [ $( { $pname : $ptype = $detail } )* ]
+                        {
+                            let slf = $crate::PyObject::from_borrowed_ptr(py, slf).unchecked_cast_into::<$class>();
+                            let ret = slf.$f(py $(, $pname )* );
+                            $crate::PyDrop::release_ref(slf, py);
+                            ret
+                        })
+                })
+        }
+        let method_def = unsafe {
+            py_method_def!(stringify!($f), 0,
+                wrap_instance_method,
+                wrap_instance_method_fast)
+        };
+        py_register_signature!($py, method_def, stringify!($f), $ret,
+            [ $( { $pname : $ptype = $detail } )* ]);
         unsafe {
-            let method_def = py_method_def!(stringify!($f), 0, wrap_instance_method);
             $crate::py_class::members::create_instance_method_descriptor::<$class>(method_def)
         }
     }}
@@ -114,7 +141,7 @@ impl <T> TypeMember<T> for InstanceMethodDescriptor<T> where T: PythonObject {
 #[macro_export]
 #[doc(hidden)]
 macro_rules! py_class_class_method {
-    ($py:ident, $class:ident :: $f:ident [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ]) => {{
+    ($py:ident, $class:ident :: $f:ident, $ret:tt [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ]) => {{
         unsafe extern "C" fn wrap_class_method(
             cls: *mut $crate::_detail::ffi::PyObject,
             args: *mut $crate::_detail::ffi::PyObject,
@@ -135,10 +162,36 @@ macro_rules! py_class_class_method {
                         })
                 })
         }
-        unsafe {
-            let method_def = py_method_def!(stringify!($f),
+        unsafe extern "C" fn wrap_class_method_fast(
+            cls: *mut $crate::_detail::ffi::PyObject,
+            args: *mut *mut $crate::_detail::ffi::PyObject,
+            nargs: $crate::_detail::ffi::Py_ssize_t,
+            kwnames: *mut $crate::_detail::ffi::PyObject)
+        -> *mut $crate::_detail::ffi::PyObject
+        {
+            const LOCATION: &'static str = concat!(stringify!($class), ".", stringify!($f), "()");
+            $crate::_detail::handle_callback(
+                LOCATION, $crate::_detail::PyObjectCallbackConverter,
+                |py| {
+                    py_argparse_fastcall_raw!(py, Some(LOCATION), args, nargs, kwnames,
+                        [ $( { $pname : $ptype = $detail } )* ]
+                        {
+                            let cls = $crate::PyObject::from_borrowed_ptr(py, cls).unchecked_cast_into::<$crate::PyType>();
+                            let ret = $class::$f(&cls, py $(, $pname )* );
+                            $crate::PyDrop::release_ref(cls, py);
+                            ret
+                        })
+                })
+        }
+        let method_def = unsafe {
+            py_method_def!(stringify!($f),
                 $crate::_detail::ffi::METH_CLASS,
-                wrap_class_method);
+                wrap_class_method,
+                wrap_class_method_fast)
+        };
+        py_register_signature!($py, method_def, stringify!($f), $ret,
+            [ $( { $pname : $ptype = $detail } )* ]);
+        unsafe {
             $crate::py_class::members::create_class_method_descriptor(method_def)
         }
     }}
@@ -164,7 +217,7 @@ impl <T> TypeMember<T> for ClassMethodDescriptor where T: PythonObject {
 #[macro_export]
 #[doc(hidden)]
 macro_rules! py_class_static_method {
-    ($py:ident, $class:ident :: $f:ident [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ]) => {{
+    ($py:ident, $class:ident :: $f:ident, $ret:tt [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ]) => {{
         unsafe extern "C" fn wrap_static_method(
             _slf: *mut $crate::_detail::ffi::PyObject,
             args: *mut $crate::_detail::ffi::PyObject,
@@ -182,12 +235,33 @@ macro_rules! py_class_static_method {
                         })
                 })
         }
-        unsafe {
-            let method_def = py_method_def!(stringify!($f),
-                $crate::_detail::ffi::METH_STATIC,
-                wrap_static_method);
-            $crate::_detail::py_fn_impl($py, method_def)
+        unsafe extern "C" fn wrap_static_method_fast(
+            _slf: *mut $crate::_detail::ffi::PyObject,
+            args: *mut *mut $crate::_detail::ffi::PyObject,
+            nargs: $crate::_detail::ffi::Py_ssize_t,
+            kwnames: *mut $crate::_detail::ffi::PyObject)
+        -> *mut $crate::_detail::ffi::PyObject
+        {
+            const LOCATION: &'static str = concat!(stringify!($class), ".", stringify!($f), "()");
+            $crate::_detail::handle_callback(
+                LOCATION, $crate::_detail::PyObjectCallbackConverter,
+                |py| {
+                    py_argparse_fastcall_raw!(py, Some(LOCATION), args, nargs, kwnames,
+                        [ $( { $pname : $ptype = $detail } )* ]
+                        {
+                            $class::$f(py $(, $pname )* )
+                        })
+                })
         }
+        let method_def = unsafe {
+            py_method_def!(stringify!($f),
+                $crate::_detail::ffi::METH_STATIC,
+                wrap_static_method,
+                wrap_static_method_fast)
+        };
+        py_register_signature!($py, method_def, stringify!($f), $ret,
+            [ $( { $pname : $ptype = $detail } )* ]);
+        unsafe { $crate::_detail::py_fn_impl($py, method_def) }
     }}
 }
 
@@ -16,14 +16,18 @@
 // OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-mod py_class;
+// `#[macro_use]` makes the `py_class!` macros available to `iterator`.
+#[macro_use] mod py_class;
 #[cfg(feature="python27-sys")]
-mod py_class_impl2;
+#[macro_use] mod py_class_impl2;
 #[cfg(feature="python3-sys")]
-mod py_class_impl3;
-#[doc(hidden)] pub mod slots;
-#[doc(hidden)] pub mod members;
-pub mod gc;
+#[macro_use] mod py_class_impl3;
+#[doc(hidden)] #[macro_use] pub mod slots;
+#[doc(hidden)] #[macro_use] pub mod members;
+#[macro_use] pub mod gc;
+mod iterator;
+
+pub use self::iterator::PyRustIterator;
 
 use libc;
 use std::{mem, ptr, cell};
@@ -32,7 +36,7 @@ use objects::{PyObject, PyType};
 use err::{self, PyResult};
 use ffi;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompareOp {
     Lt = ffi::Py_LT as isize,
     Le = ffi::Py_LE as isize,
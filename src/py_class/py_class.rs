@@ -408,7 +408,8 @@ macro_rules! py_class {
                     /* traverse_proc: */ None,
                     /* traverse_data: */ [ /*name*/ ]
                 },
-                /* data: */ [ /* { offset, name, type } */ ]
+                /* data: */ [ /* { offset, name, type } */ ],
+                /* new_signature: */ { /* parameter list of __new__ */ }
                 // TODO: base type, documentation, ...
             }
             /* slots: */ {
@@ -437,7 +438,8 @@ macro_rules! py_class {
                     /* traverse_proc: */ None,
                     /* traverse_data: */ [ /*name*/ ]
                 },
-                /* data: */ [ /* { offset, name, type } */ ]
+                /* data: */ [ /* { offset, name, type } */ ],
+                /* new_signature: */ { /* parameter list of __new__ */ }
                 // TODO: base type, documentation, ...
             }
             /* slots: */ {
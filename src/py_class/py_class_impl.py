@@ -45,7 +45,8 @@ base_case = '''
             $size:expr,
             { $( $class_visibility:tt )* },
             $gc:tt,
-            /* data: */ [ $( { $data_offset:expr, $data_name:ident, $data_ty:ty } )* ]
+            /* data: */ [ $( { $data_offset:expr, $data_name:ident, $data_ty:ty } )* ],
+            $new_signature:tt
         }
         $slots:tt { $( $imp:item )* } $members:tt
     } => {
@@ -179,6 +180,7 @@ base_case = '''
                     fn init($py: $crate::Python) -> $crate::PyResult<$crate::PyType> {
                         py_class_type_object_dynamic_init!($class, $py, TYPE_OBJECT, $slots);
                         py_class_init_members!($class, $py, TYPE_OBJECT, $members);
+                        py_class_register_new_signature!($class, $py, TYPE_OBJECT, $new_signature);
                         unsafe {
                             if $crate::_detail::ffi::PyType_Ready(&mut TYPE_OBJECT) == 0 {
                                 Ok($crate::PyType::from_type_ptr($py, &mut TYPE_OBJECT))
@@ -248,7 +250,8 @@ def generate_case(pattern, old_info=None, new_info=None, new_impl=None, new_slot
         write('$size: expr,\n')
         write('$class_visibility: tt,\n')
         write('$gc: tt,\n')
-        write('[ $( $data:tt )* ]\n')
+        write('[ $( $data:tt )* ],\n')
+        write('$new_signature: tt\n')
         write('}\n')
     else:
         write('$info:tt')
@@ -339,7 +342,8 @@ def data_decl():
                     $data_name,
                     $data_type
                 }
-            ]
+            ],
+            $new_signature
         }
         ''',
         new_impl='''
@@ -357,7 +361,8 @@ def data_decl():
         ''')
 
 def generate_class_method(special_name=None, decoration='',
-        slot=None, add_member=False, value_macro=None, value_args=None):
+        slot=None, add_member=False, value_macro=None, value_args=None,
+        new_signature=False):
     name_pattern = special_name or '$name:ident'
     name_use = special_name or '$name'
     def impl(with_params):
@@ -369,11 +374,26 @@ def generate_class_method(special_name=None, decoration='',
             }''' % name_use
             value = 'py_argparse_parse_plist_impl!{%s {%s} [] ($($p)+,)}' \
                     % (value_macro, value_args)
+            plist = '($($p)+,)'
         else:
             param_pattern = ''
             impl = 'py_class_impl_item! { $class, $py,%s($cls: &$crate::PyType,) $res_type; { $($body)* } [] }' \
                 % name_use
             value = '%s!{%s []}' % (value_macro, value_args)
+            plist = '()'
+        info = None
+        if new_signature:
+            # remember the parameter list so that the signature can be registered for stub generation
+            info = '''
+            /* info: */ {
+                $base_type,
+                $size,
+                $class_visibility,
+                $gc,
+                [ $( $data )* ],
+                /* new_signature: */ { %s { $res_type } }
+            }
+            ''' % plist
         pattern = '%s def %s ($cls:ident%s) -> $res_type:ty { $( $body:tt )* }' \
             % (decoration, name_pattern, param_pattern)
         slots = []
@@ -382,7 +402,7 @@ def generate_class_method(special_name=None, decoration='',
         members = []
         if add_member:
             members.append((name_use, value))
-        generate_case(pattern, new_impl=impl, new_slots=slots, new_members=members)
+        generate_case(pattern, new_info=info, new_impl=impl, new_slots=slots, new_members=members)
     impl(False) # without parameters
     impl(True) # with parameters
 
@@ -397,7 +417,8 @@ def traverse_and_clear():
                 /* traverse_proc: */ None,
                 $traverse_data: tt
             },
-            $datas: tt
+            $datas: tt,
+            $new_signature: tt
         }
         ''',
         new_info='''
@@ -409,7 +430,8 @@ def traverse_and_clear():
                 /* traverse_proc: */ $class::__traverse__,
                 $traverse_data
             },
-            $datas
+            $datas,
+            $new_signature
         }
         ''',
         new_impl='''
@@ -474,7 +496,7 @@ def static_method():
         ''',
         new_members=[('$name', '''
             py_argparse_parse_plist!{
-                py_class_static_method {$py, $class::$name}
+                py_class_static_method {$py, $class::$name, { $res_type }}
                 ($($p)*)
             }
         ''')])
@@ -601,7 +623,8 @@ special_names = {
     '__new__': special_class_method(
         slot='tp_new',
         value_macro='py_class_wrap_newfunc',
-        value_args='$class::__new__'),
+        value_args='$class::__new__',
+        new_signature=True),
     '__del__': error('__del__ is not supported by py_class!; Use a data member with a Drop impl instead.'),
     '__repr__': operator('tp_repr', res_type="PyString"),
     '__str__': operator('tp_str', res_type="PyString"),
@@ -767,11 +790,11 @@ def main():
     generate_instance_method(
         add_member=True,
         value_macro='py_class_instance_method',
-        value_args='$py, $class::$name')
+        value_args='$py, $class::$name, { $res_type }')
     generate_class_method(decoration='@classmethod',
         add_member=True,
         value_macro='py_class_class_method',
-        value_args='$py, $class::$name')
+        value_args='$py, $class::$name, { $res_type }')
     static_method()
     static_data()
     print(macro_end)
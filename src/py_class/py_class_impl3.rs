@@ -37,7 +37,8 @@ macro_rules! py_class_impl {
             $size:expr,
             { $( $class_visibility:tt )* },
             $gc:tt,
-            /* data: */ [ $( { $data_offset:expr, $data_name:ident, $data_ty:ty } )* ]
+            /* data: */ [ $( { $data_offset:expr, $data_name:ident, $data_ty:ty } )* ],
+            $new_signature:tt
         }
         $slots:tt { $( $imp:item )* } $members:tt
     } => {
@@ -171,6 +172,7 @@ macro_rules! py_class_impl {
                     fn init($py: $crate::Python) -> $crate::PyResult<$crate::PyType> {
                         py_class_type_object_dynamic_init!($class, $py, TYPE_OBJECT, $slots);
                         py_class_init_members!($class, $py, TYPE_OBJECT, $members);
+                        py_class_register_new_signature!($class, $py, TYPE_OBJECT, $new_signature);
                         unsafe {
                             if $crate::_detail::ffi::PyType_Ready(&mut TYPE_OBJECT) == 0 {
                                 Ok($crate::PyType::from_type_ptr($py, &mut TYPE_OBJECT))
@@ -191,7 +193,8 @@ macro_rules! py_class_impl {
             $size: expr,
             $class_visibility: tt,
             $gc: tt,
-            [ $( $data:tt )* ]
+            [ $( $data:tt )* ],
+            $new_signature: tt
         }
         $slots:tt
         { $( $imp:item )* }
@@ -211,7 +214,8 @@ macro_rules! py_class_impl {
                     $data_name,
                     $data_type
                 }
-            ]
+            ],
+            $new_signature
         }
         $slots
         /* impl: */ {
@@ -240,7 +244,8 @@ macro_rules! py_class_impl {
                 /* traverse_proc: */ None,
                 $traverse_data: tt
             },
-            $datas: tt
+            $datas: tt,
+            $new_signature: tt
         }
         $slots:tt
         { $( $imp:item )* }
@@ -256,7 +261,8 @@ macro_rules! py_class_impl {
                 /* traverse_proc: */ $class::__traverse__,
                 $traverse_data
             },
-            $datas
+            $datas,
+            $new_signature
         }
         $slots
         /* impl: */ {
@@ -1272,7 +1278,15 @@ macro_rules! py_class_impl {
         py_error! { "Invalid signature for operator __neg__" }
     };
     { {  def __new__ ($cls:ident) -> $res_type:ty { $( $body:tt )* } $($tail:tt)* }
-        $class:ident $py:ident $info:tt
+        $class:ident $py:ident
+        /* info: */ {
+            $base_type: ty,
+            $size: expr,
+            $class_visibility: tt,
+            $gc: tt,
+            [ $( $data:tt )* ],
+            $new_signature: tt
+        }
         /* slots: */ {
             /* type_slots */ [ $( $tp_slot_name:ident : $tp_slot_value:expr, )* ]
             $as_number:tt $as_sequence:tt $as_mapping:tt $setdelitem:tt
@@ -1281,7 +1295,15 @@ macro_rules! py_class_impl {
         $members:tt
     } => { py_class_impl! {
         { $($tail)* }
-        $class $py $info
+        $class $py
+        /* info: */ {
+            $base_type,
+            $size,
+            $class_visibility,
+            $gc,
+            [ $( $data )* ],
+            /* new_signature: */ { () { $res_type } }
+        }
         /* slots: */ {
             /* type_slots */ [
                 $( $tp_slot_name : $tp_slot_value, )*
@@ -1296,7 +1318,15 @@ macro_rules! py_class_impl {
         $members
     }};
     { {  def __new__ ($cls:ident, $($p:tt)+) -> $res_type:ty { $( $body:tt )* } $($tail:tt)* }
-        $class:ident $py:ident $info:tt
+        $class:ident $py:ident
+        /* info: */ {
+            $base_type: ty,
+            $size: expr,
+            $class_visibility: tt,
+            $gc: tt,
+            [ $( $data:tt )* ],
+            $new_signature: tt
+        }
         /* slots: */ {
             /* type_slots */ [ $( $tp_slot_name:ident : $tp_slot_value:expr, )* ]
             $as_number:tt $as_sequence:tt $as_mapping:tt $setdelitem:tt
@@ -1305,7 +1335,15 @@ macro_rules! py_class_impl {
         $members:tt
     } => { py_class_impl! {
         { $($tail)* }
-        $class $py $info
+        $class $py
+        /* info: */ {
+            $base_type,
+            $size,
+            $class_visibility,
+            $gc,
+            [ $( $data )* ],
+            /* new_signature: */ { ($($p)+,) { $res_type } }
+        }
         /* slots: */ {
             /* type_slots */ [
                 $( $tp_slot_name : $tp_slot_value, )*
@@ -1716,7 +1754,7 @@ macro_rules! py_class_impl {
         }
         /* members: */ {
             $( $member_name = $member_expr; )*
-            $name = py_class_instance_method!{$py, $class::$name []};
+            $name = py_class_instance_method!{$py, $class::$name, { $res_type } []};
         }
     }};
     { {  def $name:ident (&$slf:ident, $($p:tt)+) -> $res_type:ty { $( $body:tt )* } $($tail:tt)* }
@@ -1735,7 +1773,7 @@ macro_rules! py_class_impl {
         }
         /* members: */ {
             $( $member_name = $member_expr; )*
-            $name = py_argparse_parse_plist_impl!{py_class_instance_method {$py, $class::$name} [] ($($p)+,)};
+            $name = py_argparse_parse_plist_impl!{py_class_instance_method {$py, $class::$name, { $res_type }} [] ($($p)+,)};
         }
     }};
     { { @classmethod def $name:ident ($cls:ident) -> $res_type:ty { $( $body:tt )* } $($tail:tt)* }
@@ -1751,7 +1789,7 @@ macro_rules! py_class_impl {
         }
         /* members: */ {
             $( $member_name = $member_expr; )*
-            $name = py_class_class_method!{$py, $class::$name []};
+            $name = py_class_class_method!{$py, $class::$name, { $res_type } []};
         }
     }};
     { { @classmethod def $name:ident ($cls:ident, $($p:tt)+) -> $res_type:ty { $( $body:tt )* } $($tail:tt)* }
@@ -1770,7 +1808,7 @@ macro_rules! py_class_impl {
         }
         /* members: */ {
             $( $member_name = $member_expr; )*
-            $name = py_argparse_parse_plist_impl!{py_class_class_method {$py, $class::$name} [] ($($p)+,)};
+            $name = py_argparse_parse_plist_impl!{py_class_class_method {$py, $class::$name, { $res_type }} [] ($($p)+,)};
         }
     }};
     { { @staticmethod def $name:ident ($($p:tt)*) -> $res_type:ty { $( $body:tt )* } $($tail:tt)* }
@@ -1791,7 +1829,7 @@ macro_rules! py_class_impl {
             $( $member_name = $member_expr; )*
             $name = 
             py_argparse_parse_plist!{
-                py_class_static_method {$py, $class::$name}
+                py_class_static_method {$py, $class::$name, { $res_type }}
                 ($($p)*)
             }
             ;
@@ -139,6 +139,28 @@ macro_rules! py_class_wrap_newfunc {
     }}
 }
 
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_class_register_new_signature {
+    // no __new__
+    ($class:ident, $py:ident, $type_object:ident, {}) => {};
+    ($class:ident, $py:ident, $type_object:ident, { () $ret:tt }) => {
+        py_class_new_signature! { $class, $py, $type_object, $ret [] }
+    };
+    ($class:ident, $py:ident, $type_object:ident, { $plist:tt $ret:tt }) => {
+        py_argparse_parse_plist_impl! { py_class_new_signature { $class, $py, $type_object, $ret } [] $plist }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_class_new_signature {
+    ($class:ident, $py:ident, $type_object:ident, $ret:tt [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ]) => {
+        py_register_signature!($py, unsafe { &$type_object }, "__new__", $ret,
+            [ $( { $pname : $ptype = $detail } )* ]);
+    };
+}
+
 
 #[macro_export]
 #[doc(hidden)]
@@ -0,0 +1,953 @@
+// Copyright (c) 2017 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Conversions between Python objects and Rust types implementing
+//! `serde::Serialize` / `serde::Deserialize`.
+//!
+//! This module is only available with the `serde` feature.
+//!
+//! | Rust                               | Python                        |
+//! |------------------------------------|-------------------------------|
+//! | `bool`, integers, floats           | `bool`, `int`, `float`        |
+//! | `char`, `String`, `&str`           | `str`                         |
+//! | byte buffers (`serde_bytes`)       | `bytes`                       |
+//! | `None`, `()`, unit structs         | `None`                        |
+//! | `Vec<T>` and other sequences       | `list`                        |
+//! | tuples and tuple structs           | `tuple`                       |
+//! | maps and structs                   | `dict`                        |
+//! | unit enum variants                 | `"Variant"`                   |
+//! | other enum variants                | `{"Variant": value}`          |
+//!
+//! When extracting, any sequence is accepted for sequences and tuples,
+//! and any mapping is accepted for maps and structs.
+//!
+//! Errors are reported as a `PyErr`, whose message starts with the path of the
+//! field that failed to convert (e.g. `servers[1].port: ...`).
+//!
+//! # Example
+//! ```
+//! #[macro_use] extern crate serde_derive;
+//! extern crate cpython;
+//!
+//! use cpython::Python;
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Config {
+//!     name: String,
+//!     ports: Vec<u16>,
+//! }
+//!
+//! fn main() {
+//!     let gil = Python::acquire_gil();
+//!     let py = gil.python();
+//!     let config = Config { name: "web".to_owned(), ports: vec![80, 443] };
+//!     let obj = cpython::serde::to_object(py, &config).unwrap();
+//!     assert_eq!(config, cpython::serde::from_object::<Config>(py, &obj).unwrap());
+//! }
+//! ```
+
+extern crate serde;
+
+use std::{error, fmt, vec};
+use self::serde::ser::{self, Serialize};
+use self::serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use python::{Python, PythonObject, PyClone};
+use conversion::ToPyObject;
+use err::{self, PyErr, PyResult};
+use objectprotocol::ObjectProtocol;
+use objects::{exc, PyObject, PyBool, PyBytes, PyByteArray, PyDict, PyFloat, PyInt, PyList,
+              PyLong, PyString, PyTuple};
+
+/// Converts a Rust value into a Python object.
+pub fn to_object<T>(py: Python, value: &T) -> PyResult<PyObject>
+    where T: ?Sized + Serialize
+{
+    value.serialize(Serializer { py: py }).map_err(|e| e.into_pyerr(py))
+}
+
+/// Converts a Python object into a Rust value.
+pub fn from_object<T>(py: Python, obj: &PyObject) -> PyResult<T>
+    where T: DeserializeOwned
+{
+    T::deserialize(Deserializer { py: py, obj: obj }).map_err(|e| e.into_pyerr(py))
+}
+
+/// A step in the path to the value that failed to convert.
+#[derive(Debug)]
+enum PathSegment {
+    /// A struct field or string key
+    Field(String),
+    /// The repr() of a non-string key
+    Key(String),
+    /// A sequence index
+    Index(usize)
+}
+
+impl PathSegment {
+    fn for_key(py: Python, key: &PyObject) -> PathSegment {
+        if let Ok(s) = key.cast_as::<PyString>(py) {
+            return PathSegment::Field(s.to_string_lossy(py).into_owned());
+        }
+        match key.repr(py) {
+            Ok(repr) => PathSegment::Key(repr.to_string_lossy(py).into_owned()),
+            Err(_) => PathSegment::Key(String::from("?"))
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    Python(PyErr),
+    TypeError(String),
+    ValueError(String)
+}
+
+/// Error type used internally by the serializer and deserializer;
+/// converted into a `PyErr` when returned to the caller.
+#[derive(Debug)]
+struct Error {
+    kind: ErrorKind,
+    /// Path to the failing value, innermost segment first
+    path: Vec<PathSegment>
+}
+
+impl Error {
+    fn new(kind: ErrorKind) -> Error {
+        Error { kind: kind, path: Vec::new() }
+    }
+
+    fn at(mut self, segment: PathSegment) -> Error {
+        self.path.push(segment);
+        self
+    }
+
+    fn path(&self) -> String {
+        let mut path = String::new();
+        for segment in self.path.iter().rev() {
+            match *segment {
+                PathSegment::Field(ref name) if path.is_empty() => path.push_str(name),
+                PathSegment::Field(ref name) => { path.push('.'); path.push_str(name) },
+                PathSegment::Key(ref repr) => { path.push('['); path.push_str(repr); path.push(']') },
+                PathSegment::Index(index) => path.push_str(&format!("[{}]", index))
+            }
+        }
+        path
+    }
+
+    fn into_pyerr(self, py: Python) -> PyErr {
+        let path = self.path();
+        let with_path = |message: String| {
+            if path.is_empty() { message } else { format!("{}: {}", path, message) }
+        };
+        match self.kind {
+            ErrorKind::Python(err) => {
+                if path.is_empty() { err } else { err::add_context(py, err, &path) }
+            }
+            ErrorKind::TypeError(message) => PyErr::new::<exc::TypeError, _>(py, with_path(message)),
+            ErrorKind::ValueError(message) => PyErr::new::<exc::ValueError, _>(py, with_path(message))
+        }
+    }
+}
+
+impl From<PyErr> for Error {
+    fn from(err: PyErr) -> Error {
+        Error::new(ErrorKind::Python(err))
+    }
+}
+
+impl<'p> From<::python::PythonObjectDowncastError<'p>> for Error {
+    fn from(err: ::python::PythonObjectDowncastError<'p>) -> Error {
+        Error::from(PyErr::from(err))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ErrorKind::Python(_) => f.write_str("Python exception"),
+            ErrorKind::TypeError(ref message) | ErrorKind::ValueError(ref message) => f.write_str(message)
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error::new(ErrorKind::ValueError(msg.to_string()))
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error::new(ErrorKind::ValueError(msg.to_string()))
+    }
+
+    fn invalid_type(unexp: de::Unexpected, exp: &de::Expected) -> Error {
+        Error::new(ErrorKind::TypeError(format!("invalid type: {}, expected {}", unexp, exp)))
+    }
+}
+
+struct Serializer<'p> {
+    py: Python<'p>
+}
+
+impl <'p> Serializer<'p> {
+    fn object<T: ToPyObject>(self, value: T) -> Result<PyObject, Error> {
+        Ok(value.into_py_object(self.py).into_object())
+    }
+}
+
+impl <'p> ser::Serializer for Serializer<'p> {
+    type Ok = PyObject;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'p>;
+    type SerializeTuple = SeqSerializer<'p>;
+    type SerializeTupleStruct = SeqSerializer<'p>;
+    type SerializeTupleVariant = VariantSerializer<SeqSerializer<'p>>;
+    type SerializeMap = MapSerializer<'p>;
+    type SerializeStruct = MapSerializer<'p>;
+    type SerializeStructVariant = VariantSerializer<MapSerializer<'p>>;
+
+    fn serialize_bool(self, v: bool) -> Result<PyObject, Error> { self.object(v) }
+    fn serialize_i8(self, v: i8) -> Result<PyObject, Error> { self.object(v) }
+    fn serialize_i16(self, v: i16) -> Result<PyObject, Error> { self.object(v) }
+    fn serialize_i32(self, v: i32) -> Result<PyObject, Error> { self.object(v) }
+    fn serialize_i64(self, v: i64) -> Result<PyObject, Error> { self.object(v) }
+    #[cfg(feature="i128")]
+    fn serialize_i128(self, v: i128) -> Result<PyObject, Error> { self.object(v) }
+    fn serialize_u8(self, v: u8) -> Result<PyObject, Error> { self.object(v) }
+    fn serialize_u16(self, v: u16) -> Result<PyObject, Error> { self.object(v) }
+    fn serialize_u32(self, v: u32) -> Result<PyObject, Error> { self.object(v) }
+    fn serialize_u64(self, v: u64) -> Result<PyObject, Error> { self.object(v) }
+    #[cfg(feature="i128")]
+    fn serialize_u128(self, v: u128) -> Result<PyObject, Error> { self.object(v) }
+    fn serialize_f32(self, v: f32) -> Result<PyObject, Error> { self.object(v) }
+    fn serialize_f64(self, v: f64) -> Result<PyObject, Error> { self.object(v) }
+    fn serialize_char(self, v: char) -> Result<PyObject, Error> { self.object(v.to_string()) }
+    fn serialize_str(self, v: &str) -> Result<PyObject, Error> { self.object(v) }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<PyObject, Error> {
+        Ok(PyBytes::new(self.py, v).into_object())
+    }
+
+    fn serialize_none(self) -> Result<PyObject, Error> {
+        Ok(self.py.None())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<PyObject, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<PyObject, Error> {
+        Ok(self.py.None())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<PyObject, Error> {
+        Ok(self.py.None())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str)
+        -> Result<PyObject, Error>
+    {
+        self.object(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T)
+        -> Result<PyObject, Error>
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _name: &'static str, _index: u32,
+                                                        variant: &'static str, value: &T)
+        -> Result<PyObject, Error>
+    {
+        let py = self.py;
+        let value = try!(value.serialize(self).map_err(|e| e.at(PathSegment::Field(variant.to_owned()))));
+        let dict = PyDict::new(py);
+        try!(dict.set_item(py, variant, value));
+        Ok(dict.into_object())
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer<'p>, Error> {
+        Ok(SeqSerializer { py: self.py, items: Vec::with_capacity(len.unwrap_or(0)), tuple: false })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<'p>, Error> {
+        Ok(SeqSerializer { py: self.py, items: Vec::with_capacity(len), tuple: true })
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqSerializer<'p>, Error> {
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize)
+        -> Result<VariantSerializer<SeqSerializer<'p>>, Error>
+    {
+        Ok(VariantSerializer { variant: variant, inner: try!(self.serialize_tuple(len)) })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer<'p>, Error> {
+        Ok(MapSerializer { py: self.py, dict: PyDict::new(self.py), key: None })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapSerializer<'p>, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize)
+        -> Result<VariantSerializer<MapSerializer<'p>>, Error>
+    {
+        Ok(VariantSerializer { variant: variant, inner: try!(self.serialize_map(Some(len))) })
+    }
+}
+
+struct SeqSerializer<'p> {
+    py: Python<'p>,
+    items: Vec<PyObject>,
+    /// Whether to create a tuple instead of a list
+    tuple: bool
+}
+
+impl <'p> SeqSerializer<'p> {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let index = self.items.len();
+        let item = try!(value.serialize(Serializer { py: self.py }).map_err(|e| e.at(PathSegment::Index(index))));
+        self.items.push(item);
+        Ok(())
+    }
+
+    fn finish(self) -> PyObject {
+        if self.tuple {
+            PyTuple::new(self.py, &self.items).into_object()
+        } else {
+            PyList::new(self.py, &self.items).into_object()
+        }
+    }
+}
+
+impl <'p> ser::SerializeSeq for SeqSerializer<'p> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<PyObject, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl <'p> ser::SerializeTuple for SeqSerializer<'p> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<PyObject, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl <'p> ser::SerializeTupleStruct for SeqSerializer<'p> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<PyObject, Error> {
+        Ok(self.finish())
+    }
+}
+
+struct MapSerializer<'p> {
+    py: Python<'p>,
+    dict: PyDict,
+    key: Option<PyObject>
+}
+
+impl <'p> MapSerializer<'p> {
+    fn insert<T: ?Sized + Serialize>(&mut self, key: PyObject, value: &T) -> Result<(), Error> {
+        let py = self.py;
+        match value.serialize(Serializer { py: py }) {
+            Ok(value) => self.dict.set_item(py, key, value).map_err(Error::from),
+            Err(e) => Err(e.at(PathSegment::for_key(py, &key)))
+        }
+    }
+}
+
+impl <'p> ser::SerializeMap for MapSerializer<'p> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(try!(key.serialize(Serializer { py: self.py })));
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.key.take().expect("serialize_value called before serialize_key");
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<PyObject, Error> {
+        Ok(self.dict.into_object())
+    }
+}
+
+impl <'p> ser::SerializeStruct for MapSerializer<'p> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        let key = PyString::new(self.py, key).into_object();
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<PyObject, Error> {
+        Ok(self.dict.into_object())
+    }
+}
+
+/// Serializes the contents of an enum variant, and wraps them in `{variant: contents}`.
+struct VariantSerializer<S> {
+    variant: &'static str,
+    inner: S
+}
+
+impl <S> VariantSerializer<S> {
+    fn finish(py: Python, variant: &'static str, value: PyObject) -> Result<PyObject, Error> {
+        let dict = PyDict::new(py);
+        try!(dict.set_item(py, variant, value));
+        Ok(dict.into_object())
+    }
+
+    fn at_variant(&self, err: Error) -> Error {
+        err.at(PathSegment::Field(self.variant.to_owned()))
+    }
+}
+
+impl <'p> ser::SerializeTupleVariant for VariantSerializer<SeqSerializer<'p>> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        match self.inner.push(value) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(self.at_variant(e))
+        }
+    }
+
+    fn end(self) -> Result<PyObject, Error> {
+        let py = self.inner.py;
+        VariantSerializer::<()>::finish(py, self.variant, self.inner.finish())
+    }
+}
+
+impl <'p> ser::SerializeStructVariant for VariantSerializer<MapSerializer<'p>> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        match ser::SerializeStruct::serialize_field(&mut self.inner, key, value) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(self.at_variant(e))
+        }
+    }
+
+    fn end(self) -> Result<PyObject, Error> {
+        let py = self.inner.py;
+        VariantSerializer::<()>::finish(py, self.variant, self.inner.dict.into_object())
+    }
+}
+
+struct Deserializer<'a, 'p> {
+    py: Python<'p>,
+    obj: &'a PyObject
+}
+
+macro_rules! deserialize_extract(
+    ($method: ident, $visit: ident, $t: ty) => (
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.$visit(try!(self.obj.extract::<$t>(self.py)))
+        }
+    )
+);
+
+impl <'a, 'p> Deserializer<'a, 'p> {
+    fn is_none(&self) -> bool {
+        *self.obj == self.py.None()
+    }
+
+    /// Gets the elements of a sequence; strings are not accepted.
+    fn sequence_items(&self) -> Result<Vec<PyObject>, Error> {
+        let py = self.py;
+        if let Ok(list) = self.obj.cast_as::<PyList>(py) {
+            return Ok(list.iter(py).collect());
+        }
+        if let Ok(tuple) = self.obj.cast_as::<PyTuple>(py) {
+            return Ok(tuple.as_slice(py).iter().map(|item| item.clone_ref(py)).collect());
+        }
+        if self.obj.cast_as::<PyString>(py).is_ok() || !try!(self.obj.hasattr(py, "__len__")) {
+            return Err(self.type_error("a sequence"));
+        }
+        let mut items = Vec::new();
+        for item in try!(self.obj.iter(py)) {
+            items.push(try!(item));
+        }
+        Ok(items)
+    }
+
+    /// Gets the items of a mapping.
+    fn mapping_items(&self) -> Result<Vec<(PyObject, PyObject)>, Error> {
+        let py = self.py;
+        if let Ok(dict) = self.obj.cast_as::<PyDict>(py) {
+            return Ok(dict.items(py));
+        }
+        if !try!(self.obj.hasattr(py, "items")) {
+            return Err(self.type_error("a mapping"));
+        }
+        let dict = try!(try!(py.get_type::<PyDict>().call(py, (self.obj,), None)).cast_into::<PyDict>(py));
+        Ok(dict.items(py))
+    }
+
+    /// Deserializes an integer that does not fit into `i64` or `u64`.
+    #[cfg(feature="i128")]
+    fn deserialize_large_int<'de, V: Visitor<'de>>(&self, visitor: V) -> Result<V::Value, Error> {
+        if let Ok(v) = self.obj.extract::<i128>(self.py) {
+            visitor.visit_i128(v)
+        } else {
+            visitor.visit_u128(try!(self.obj.extract::<u128>(self.py)))
+        }
+    }
+
+    /// Deserializes an integer that does not fit into `i64` or `u64`;
+    /// without the `i128` feature this reports the `OverflowError`.
+    #[cfg(not(feature="i128"))]
+    fn deserialize_large_int<'de, V: Visitor<'de>>(&self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(try!(self.obj.extract::<u64>(self.py)))
+    }
+
+    fn type_error(&self, expected: &str) -> Error {
+        let type_name = self.obj.get_type(self.py).name(self.py).into_owned();
+        Error::new(ErrorKind::TypeError(format!("expected {}, got '{}'", expected, type_name)))
+    }
+}
+
+impl <'de, 'a, 'p> de::Deserializer<'de> for Deserializer<'a, 'p> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let py = self.py;
+        let obj = self.obj;
+        if self.is_none() {
+            visitor.visit_unit()
+        } else if let Ok(b) = obj.cast_as::<PyBool>(py) {
+            visitor.visit_bool(b.is_true())
+        } else if obj.cast_as::<PyInt>(py).is_ok() || obj.cast_as::<PyLong>(py).is_ok() {
+            if let Ok(v) = obj.extract::<i64>(py) {
+                visitor.visit_i64(v)
+            } else if let Ok(v) = obj.extract::<u64>(py) {
+                visitor.visit_u64(v)
+            } else {
+                self.deserialize_large_int(visitor)
+            }
+        } else if let Ok(f) = obj.cast_as::<PyFloat>(py) {
+            visitor.visit_f64(f.value(py))
+        } else if obj.cast_as::<PyString>(py).is_ok() {
+            visitor.visit_string(try!(obj.extract::<String>(py)))
+        } else if let Ok(bytes) = obj.cast_as::<PyBytes>(py) {
+            visitor.visit_bytes(bytes.data(py))
+        } else if obj.cast_as::<PyDict>(py).is_ok() {
+            self.deserialize_map(visitor)
+        } else if obj.cast_as::<PyList>(py).is_ok() || obj.cast_as::<PyTuple>(py).is_ok() {
+            self.deserialize_seq(visitor)
+        } else {
+            Err(self.type_error("None, bool, int, float, str, bytes, dict, list or tuple"))
+        }
+    }
+
+    deserialize_extract!(deserialize_bool, visit_bool, bool);
+    deserialize_extract!(deserialize_i8, visit_i8, i8);
+    deserialize_extract!(deserialize_i16, visit_i16, i16);
+    deserialize_extract!(deserialize_i32, visit_i32, i32);
+    deserialize_extract!(deserialize_i64, visit_i64, i64);
+    #[cfg(feature="i128")]
+    deserialize_extract!(deserialize_i128, visit_i128, i128);
+    deserialize_extract!(deserialize_u8, visit_u8, u8);
+    deserialize_extract!(deserialize_u16, visit_u16, u16);
+    deserialize_extract!(deserialize_u32, visit_u32, u32);
+    deserialize_extract!(deserialize_u64, visit_u64, u64);
+    #[cfg(feature="i128")]
+    deserialize_extract!(deserialize_u128, visit_u128, u128);
+    deserialize_extract!(deserialize_f32, visit_f32, f32);
+    deserialize_extract!(deserialize_f64, visit_f64, f64);
+    deserialize_extract!(deserialize_str, visit_string, String);
+    deserialize_extract!(deserialize_string, visit_string, String);
+    deserialize_extract!(deserialize_identifier, visit_string, String);
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let s = try!(self.obj.extract::<String>(self.py));
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(de::Error::invalid_value(de::Unexpected::Str(&s), &"a single character"))
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let py = self.py;
+        if let Ok(bytearray) = self.obj.cast_as::<PyByteArray>(py) {
+            return visitor.visit_byte_buf(bytearray.to_vec(py));
+        }
+        visitor.visit_bytes(try!(self.obj.cast_as::<PyBytes>(py)).data(py))
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_none() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_none() {
+            visitor.visit_unit()
+        } else {
+            Err(self.type_error("None"))
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let items = try!(self.sequence_items());
+        visitor.visit_seq(SeqAccess { py: self.py, items: items.into_iter(), index: 0 })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V)
+        -> Result<V::Value, Error>
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let items = try!(self.mapping_items());
+        visitor.visit_map(MapAccess { py: self.py, items: items.into_iter(), value: None })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, _fields: &'static [&'static str], visitor: V)
+        -> Result<V::Value, Error>
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V)
+        -> Result<V::Value, Error>
+    {
+        let py = self.py;
+        if let Ok(s) = self.obj.cast_as::<PyString>(py) {
+            let variant = try!(s.to_string(py)).into_owned();
+            return visitor.visit_enum(EnumAccess { py: py, variant: variant, value: None });
+        }
+        let mut items = try!(self.mapping_items());
+        if items.len() != 1 {
+            return Err(Error::new(ErrorKind::ValueError(format!(
+                "expected a string or a dict with a single key for an enum, got a mapping with {} keys",
+                items.len()))));
+        }
+        let (key, value) = items.pop().unwrap();
+        let variant = try!(key.extract::<String>(py));
+        visitor.visit_enum(EnumAccess { py: py, variant: variant, value: Some(value) })
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+struct SeqAccess<'p> {
+    py: Python<'p>,
+    items: vec::IntoIter<PyObject>,
+    index: usize
+}
+
+impl <'de, 'p> de::SeqAccess<'de> for SeqAccess<'p> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        match self.items.next() {
+            Some(item) => {
+                let index = self.index;
+                self.index += 1;
+                seed.deserialize(Deserializer { py: self.py, obj: &item })
+                    .map(Some)
+                    .map_err(|e| e.at(PathSegment::Index(index)))
+            }
+            None => Ok(None)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct MapAccess<'p> {
+    py: Python<'p>,
+    items: vec::IntoIter<(PyObject, PyObject)>,
+    /// The key and value of the current entry
+    value: Option<(PyObject, PyObject)>
+}
+
+impl <'de, 'p> de::MapAccess<'de> for MapAccess<'p> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        match self.items.next() {
+            Some((key, value)) => {
+                let py = self.py;
+                let result = seed.deserialize(Deserializer { py: py, obj: &key })
+                    .map_err(|e| e.at(PathSegment::for_key(py, &key)));
+                self.value = Some((key, value));
+                result.map(Some)
+            }
+            None => Ok(None)
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let py = self.py;
+        let (key, value) = self.value.take().expect("next_value_seed called before next_key_seed");
+        seed.deserialize(Deserializer { py: py, obj: &value })
+            .map_err(|e| e.at(PathSegment::for_key(py, &key)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct EnumAccess<'p> {
+    py: Python<'p>,
+    variant: String,
+    /// The contents of the variant; `None` for unit variants given as a string
+    value: Option<PyObject>
+}
+
+impl <'p> EnumAccess<'p> {
+    fn deserialize<F, R>(self, f: F) -> Result<R, Error>
+        where F: FnOnce(Deserializer) -> Result<R, Error>
+    {
+        let variant = self.variant;
+        match self.value {
+            Some(ref value) => f(Deserializer { py: self.py, obj: value })
+                .map_err(|e| e.at(PathSegment::Field(variant))),
+            None => Err(de::Error::invalid_type(de::Unexpected::UnitVariant, &"a dict with the variant contents"))
+        }
+    }
+}
+
+impl <'de, 'p> de::EnumAccess<'de> for EnumAccess<'p> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant: de::value::StrDeserializer<Error> = self.variant.as_str().into_deserializer();
+        let value = try!(seed.deserialize(variant));
+        Ok((value, self))
+    }
+}
+
+impl <'de, 'p> de::VariantAccess<'de> for EnumAccess<'p> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            None => Ok(()),
+            Some(ref value) if *value == self.py.None() => Ok(()),
+            Some(_) => Err(de::Error::invalid_type(de::Unexpected::Map, &"a unit variant"))
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        self.deserialize(|de| seed.deserialize(de))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize(|de| de::Deserializer::deserialize_seq(de, visitor))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V)
+        -> Result<V::Value, Error>
+    {
+        self.deserialize(|de| de::Deserializer::deserialize_map(de, visitor))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use python::Python;
+    use objects::{exc, PyObject};
+    use objectprotocol::ObjectProtocol;
+    use super::{to_object, from_object};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+        tags: Vec<String>,
+        backup: Option<Box<Server>>
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Rect(u32, u32),
+        Polygon { sides: u8 }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        servers: Vec<Server>,
+        shapes: Vec<Shape>,
+        limits: BTreeMap<String, (i64, bool)>
+    }
+
+    fn eval(py: Python, code: &str) -> PyObject {
+        py.eval(code, None, None).unwrap()
+    }
+
+    fn check_roundtrip(py: Python, config: &Config, expected: &str) {
+        let obj = to_object(py, config).unwrap();
+        let equal = obj.getattr(py, "__eq__").unwrap().call(py, (eval(py, expected),), None).unwrap();
+        assert!(equal.extract::<bool>(py).unwrap(), "{}", obj);
+        assert_eq!(config, &from_object::<Config>(py, &obj).unwrap());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut limits = BTreeMap::new();
+        limits.insert("cpu".to_owned(), (-1, true));
+        let config = Config {
+            name: "prod".to_owned(),
+            servers: vec![Server {
+                host: "a".to_owned(), port: 80, tags: vec!["x".to_owned()],
+                backup: Some(Box::new(Server { host: "b".to_owned(), port: 81, tags: vec![], backup: None }))
+            }],
+            shapes: vec![Shape::Empty, Shape::Circle(1.5), Shape::Rect(2, 3), Shape::Polygon { sides: 5 }],
+            limits: limits
+        };
+        check_roundtrip(py, &config,
+            "{'name': 'prod', \
+              'servers': [{'host': 'a', 'port': 80, 'tags': ['x'], \
+                           'backup': {'host': 'b', 'port': 81, 'tags': [], 'backup': None}}], \
+              'shapes': ['Empty', {'Circle': 1.5}, {'Rect': (2, 3)}, {'Polygon': {'sides': 5}}], \
+              'limits': {'cpu': (-1, True)}}");
+    }
+
+    #[test]
+    fn test_from_generic_objects() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        // tuples for sequences, lists for tuples, and non-dict mappings are accepted
+        let obj = eval(py, "{'name': 'n', 'servers': (), 'shapes': [{'Rect': [1, 2]}], \
+                             'limits': type('M', (object,), {'items': lambda self: [('a', (1, False))], \
+                                                            'keys': lambda self: ['a'], \
+                                                            '__getitem__': lambda self, k: (1, False)})()}");
+        let config = from_object::<Config>(py, &obj).unwrap();
+        assert_eq!(vec![Shape::Rect(1, 2)], config.shapes);
+        assert_eq!(Some(&(1, false)), config.limits.get("a"));
+    }
+
+    #[test]
+    fn test_error_path() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = eval(py, "{'name': 'n', 'servers': [{'host': 'a', 'port': 70000, 'tags': []}], \
+                             'shapes': [], 'limits': {}}");
+        let mut err = from_object::<Config>(py, &obj).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::OverflowError>()));
+        let message = err.instance(py).str(py).unwrap().to_string_lossy(py).into_owned();
+        assert!(message.starts_with("servers[0].port: "), "{}", message);
+
+        let obj = eval(py, "{'name': 'n', 'servers': [], 'shapes': ['Empty', {'Circle': 'big'}], 'limits': {}}");
+        let mut err = from_object::<Config>(py, &obj).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>()));
+        let message = err.instance(py).str(py).unwrap().to_string_lossy(py).into_owned();
+        assert!(message.starts_with("shapes[1].Circle: "), "{}", message);
+
+        let obj = eval(py, "{'name': 'n', 'servers': [], 'shapes': []}");
+        let mut err = from_object::<Config>(py, &obj).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::ValueError>()));
+        assert_eq!("missing field `limits`", err.instance(py).str(py).unwrap().to_string_lossy(py));
+    }
+
+    #[cfg(feature="python3-sys")]
+    #[test]
+    fn test_error_path_wraps_unicode_errors() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = eval(py, "{'name': '\\ud800', 'servers': [], 'shapes': [], 'limits': {}}");
+        let mut err = from_object::<Config>(py, &obj).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::ValueError>()));
+        let cause = err.instance(py).getattr(py, "__cause__").unwrap();
+        assert!(cause.get_type(py) == py.get_type::<exc::UnicodeEncodeError>());
+    }
+
+    #[test]
+    fn test_serialize_error_path() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        // lists are not hashable, so they can't be used as dict keys
+        let mut map = BTreeMap::new();
+        map.insert(vec![1], 2);
+        let mut outer = BTreeMap::new();
+        outer.insert("inner", map);
+        let mut err = to_object(py, &vec![outer]).unwrap_err();
+        assert!(err.matches(py, py.get_type::<exc::TypeError>()));
+        let message = err.instance(py).str(py).unwrap().to_string_lossy(py).into_owned();
+        assert!(message.starts_with("[0].inner: "), "{}", message);
+    }
+}
@@ -0,0 +1,809 @@
+// Copyright (c) 2017 Daniel Grunwald
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this
+// software and associated documentation files (the "Software"), to deal in the Software
+// without restriction, including without limitation the rights to use, copy, modify, merge,
+// publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons
+// to whom the Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED,
+// INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR
+// PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE
+// FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR
+// OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! This module contains logic for generating Python type stubs (`.pyi` files).
+//! It is only available with the `stubs` feature.
+//!
+//! With the `stubs` feature, `py_fn!()` and `py_class!()` record the signature of every
+//! function and method they create: the parameter names, the Rust parameter types,
+//! the default values and the Rust return type.
+//! `generate_stub()` looks up these signatures for the members of a module
+//! and translates the Rust types into Python type annotations
+//! (e.g. `i32` becomes `int`, `Vec<T>` becomes `List[T]`, `Option<T>` becomes `Optional[T]`
+//! and classes declared with `py_class!()` are referred to by their name).
+//!
+//! Types are recognized by the path written in the signature (`Vec<T>` or `std::vec::Vec<T>`).
+//! Other Rust types, including type aliases, are annotated as `Any`,
+//! unless an annotation was set with `register_type_annotation()`.
+//! `py_fn!()` only knows the return type of an existing function if it is declared
+//! in the macro invocation: `py_fn!(py, f(x: i32) -> PyResult<i32>)`.
+//!
+//! Signatures are recorded when the function object or the class is created,
+//! so the module must be fully initialized before generating its stub.
+//!
+//! # Example
+//! ```
+//! #[macro_use] extern crate cpython;
+//! use cpython::{Python, PyModule, PyResult};
+//!
+//! fn add(_py: Python, a: i64, b: Option<i64>) -> PyResult<i64> {
+//!     Ok(a + b.unwrap_or(0))
+//! }
+//!
+//! fn main() {
+//!     let gil = Python::acquire_gil();
+//!     let py = gil.python();
+//!     let m = PyModule::new(py, "example").unwrap();
+//!     m.add(py, "add", py_fn!(py, add(a: i64, b: Option<i64> = None) -> PyResult<i64>)).unwrap();
+//!     let stub = cpython::stubs::generate_stub(py, &m).unwrap();
+//!     assert!(stub.contains("def add(a: int, b: Optional[int] = None) -> int: ..."));
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, BTreeSet};
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Once, ONCE_INIT};
+use libc;
+use ffi;
+use python::{Python, PythonObject};
+use pythonrun::GILProtected;
+use argparse::ParamKind;
+use objects::{PyObject, PyDict, PyModule, PyType, exc};
+use objectprotocol::ObjectProtocol;
+use err::{PyErr, PyResult};
+
+/// Signature of a single parameter of a function created by `py_fn!()` or `py_class!()`.
+#[derive(Clone, Debug)]
+pub struct ParamSignature {
+    /// The name of the parameter.
+    pub name: &'static str,
+    /// The Rust type of the parameter, as written in the declaration.
+    pub type_name: &'static str,
+    /// The Rust expression used as default value, if the parameter is optional.
+    pub default: Option<&'static str>,
+    /// How the parameter receives its argument.
+    pub kind: ParamSignatureKind
+}
+
+/// How a parameter receives its argument.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParamSignatureKind {
+    /// A regular parameter.
+    Regular(ParamKind),
+    /// The parameter receives the remaining positional arguments (`*args`).
+    VarPositional,
+    /// The parameter receives the remaining keyword arguments (`**kwargs`).
+    VarKeyword
+}
+
+/// Signature of a function created by `py_fn!()` or `py_class!()`.
+#[derive(Clone, Debug)]
+pub struct FunctionSignature {
+    /// The name of the Rust function.
+    pub name: &'static str,
+    /// The parameters of the function, excluding `py` and `self`/`cls`.
+    pub params: Vec<ParamSignature>,
+    /// The Rust return type of the function, as written in the declaration.
+    /// `None` if the return type was not declared in the `py_fn!()` invocation.
+    pub return_type: Option<&'static str>
+}
+
+/// Describes how a Rust type is written in a type stub.
+///
+/// The conversions provided by this crate come with annotations;
+/// use `register_type_annotation()` for other types.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TypeAnnotation {
+    /// A builtin Python type, e.g. `Builtin("int")`.
+    Builtin(&'static str),
+    /// A type from another Python module, given as module name and type name,
+    /// e.g. `Module("datetime", "date")`. The stub imports the module.
+    Module(&'static str, &'static str),
+    /// A generic type from the `typing` module, applied to the annotations
+    /// of the Rust type arguments, e.g. `Generic("List")` for `Vec<T>`.
+    Generic(&'static str),
+    /// A union of builtin Python types, e.g. `Union(&["int", "slice"])`.
+    Union(&'static [&'static str]),
+    /// The annotation of the first Rust type argument, e.g. for `Box<T>`.
+    Transparent,
+    /// `typing.Any`
+    Any
+}
+
+/// Annotations for the types that this crate converts,
+/// keyed by the paths under which the types are usually written (without type arguments).
+/// Paths to this crate's types are written without the `cpython::` prefix.
+const TYPE_ANNOTATIONS: &'static [(&'static [&'static str], TypeAnnotation)] = &[
+    (&["i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize"],
+        TypeAnnotation::Builtin("int")),
+    (&["f32", "f64"], TypeAnnotation::Builtin("float")),
+    (&["bool"], TypeAnnotation::Builtin("bool")),
+    (&["str", "char", "String", "std::string::String"], TypeAnnotation::Builtin("str")),
+    (&["Vec", "std::vec::Vec"], TypeAnnotation::Generic("List")),
+    (&["Option", "std::option::Option"], TypeAnnotation::Generic("Optional")),
+    (&["HashMap", "std::collections::HashMap", "BTreeMap", "std::collections::BTreeMap"],
+        TypeAnnotation::Generic("Dict")),
+    (&["HashSet", "std::collections::HashSet", "BTreeSet", "std::collections::BTreeSet"],
+        TypeAnnotation::Generic("Set")),
+    // PyResult<T>, Box<T>, Cow<'a, T> etc. are represented by the wrapped type
+    (&["PyResult", "Result", "std::result::Result", "Box", "std::boxed::Box",
+       "Rc", "std::rc::Rc", "Arc", "std::sync::Arc", "Cow", "std::borrow::Cow"],
+        TypeAnnotation::Transparent),
+    (&["PyInt", "PyLong"], TypeAnnotation::Builtin("int")),
+    (&["PyFloat"], TypeAnnotation::Builtin("float")),
+    (&["PyBool"], TypeAnnotation::Builtin("bool")),
+    (&["PyString", "PyUnicode"], TypeAnnotation::Builtin("str")),
+    (&["PyBytes"], TypeAnnotation::Builtin("bytes")),
+    (&["PyByteArray"], TypeAnnotation::Builtin("bytearray")),
+    (&["PyTuple"], TypeAnnotation::Builtin("tuple")),
+    (&["PyList"], TypeAnnotation::Builtin("list")),
+    (&["PyDict"], TypeAnnotation::Builtin("dict")),
+    (&["PySet"], TypeAnnotation::Builtin("set")),
+    (&["PyFrozenSet"], TypeAnnotation::Builtin("frozenset")),
+    (&["PyType"], TypeAnnotation::Builtin("type")),
+    (&["PySlice"], TypeAnnotation::Builtin("slice")),
+    (&["SliceOrIndex"], TypeAnnotation::Union(&["int", "slice"])),
+    (&["PyComplex", "Complex", "num_complex::Complex"], TypeAnnotation::Builtin("complex")),
+    (&["BigInt", "num_bigint::BigInt", "BigUint", "num_bigint::BigUint"], TypeAnnotation::Builtin("int")),
+    (&["Path", "std::path::Path", "PathBuf", "std::path::PathBuf",
+       "OsStr", "std::ffi::OsStr", "OsString", "std::ffi::OsString"], TypeAnnotation::Builtin("str")),
+    (&["PyDate", "NaiveDate", "chrono::NaiveDate"], TypeAnnotation::Module("datetime", "date")),
+    (&["PyTime", "NaiveTime", "chrono::NaiveTime"], TypeAnnotation::Module("datetime", "time")),
+    (&["PyDateTime", "NaiveDateTime", "chrono::NaiveDateTime", "SystemTime", "std::time::SystemTime"],
+        TypeAnnotation::Module("datetime", "datetime")),
+    (&["PyDelta", "Duration", "std::time::Duration"], TypeAnnotation::Module("datetime", "timedelta")),
+    (&["PyTzInfo"], TypeAnnotation::Module("datetime", "tzinfo")),
+];
+
+/// The signatures and type annotations registered at runtime.
+struct Registry {
+    /// Maps the address of a PyMethodDef (or of the PyTypeObject for __new__) to the signature.
+    signatures: HashMap<usize, FunctionSignature>,
+    /// Type annotations registered with `register_type_annotation()`.
+    annotations: HashMap<&'static str, TypeAnnotation>
+}
+
+static REGISTRY_INIT: Once = ONCE_INIT;
+static mut REGISTRY: *const GILProtected<RefCell<Registry>> = 0 as *const _;
+
+/// Gets the registry. It is created on first use and lives until the process exits.
+fn registry(py: Python) -> &RefCell<Registry> {
+    unsafe {
+        REGISTRY_INIT.call_once(|| {
+            let registry = Registry { signatures: HashMap::new(), annotations: HashMap::new() };
+            REGISTRY = Box::into_raw(Box::new(GILProtected::new(RefCell::new(registry))));
+        });
+        (*REGISTRY).get(py)
+    }
+}
+
+/// Records the signature of a function. Used by the `py_fn!()` and `py_class!()` macros.
+///
+/// `key` is the address of the `PyMethodDef` of the function,
+/// or the address of the type object for the signature of `__new__`.
+#[doc(hidden)]
+pub fn register_signature<F>(py: Python, key: *const libc::c_void, f: F)
+    where F: FnOnce() -> FunctionSignature
+{
+    registry(py).borrow_mut().signatures.entry(key as usize).or_insert_with(f);
+}
+
+fn registered_signature(py: Python, key: usize) -> Option<FunctionSignature> {
+    registry(py).borrow().signatures.get(&key).cloned()
+}
+
+/// Sets the annotation used in type stubs for the Rust type written as `path`
+/// (without type arguments).
+///
+/// For generic types, the annotation applies to all instantiations.
+/// The path must be written the same way as in the signatures, e.g. `"Celsius"`
+/// and `"units::Celsius"` are different paths.
+/// Types without annotation (other than the classes in the module) are written as `Any`.
+///
+/// # Example
+/// ```
+/// use cpython::Python;
+/// use cpython::stubs::{register_type_annotation, TypeAnnotation};
+///
+/// let gil = Python::acquire_gil();
+/// register_type_annotation(gil.python(), "Celsius", TypeAnnotation::Builtin("float"));
+/// ```
+pub fn register_type_annotation(py: Python, path: &'static str, annotation: TypeAnnotation) {
+    registry(py).borrow_mut().annotations.insert(path.trim_start_matches("::"), annotation);
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum CallableKind {
+    Function,
+    InstanceMethod,
+    ClassMethod
+}
+
+unsafe fn type_name_of(obj: *mut ffi::PyObject) -> &'static [u8] {
+    CStr::from_ptr((*ffi::Py_TYPE(obj)).tp_name).to_bytes()
+}
+
+/// Returns the key under which the signature of `obj` is registered.
+fn signature_key(_py: Python, obj: &PyObject) -> Option<(CallableKind, usize)> {
+    let ptr = obj.as_ptr();
+    unsafe {
+        if ffi::PyCFunction_Check(ptr) != 0 {
+            let ml = (*(ptr as *mut ffi::PyCFunctionObject)).m_ml;
+            return Some((CallableKind::Function, ml as usize));
+        }
+        // PyMethodDescr_Type and PyClassMethodDescr_Type are not exported by Python 2.7
+        let kind = match type_name_of(ptr) {
+            b"method_descriptor" => CallableKind::InstanceMethod,
+            b"classmethod_descriptor" => CallableKind::ClassMethod,
+            _ => return None
+        };
+        let method = (*(ptr as *mut ffi::PyMethodDescrObject)).d_method;
+        Some((kind, method as usize))
+    }
+}
+
+/// Gets the signature of a function, method descriptor or class created by
+/// `py_fn!()` or `py_class!()`.
+///
+/// For classes, returns the signature of the `__new__` method.
+/// Returns `None` if no signature was recorded for the object.
+pub fn function_signature(py: Python, obj: &PyObject) -> Option<FunctionSignature> {
+    if let Ok(ty) = obj.cast_as::<PyType>(py) {
+        return registered_signature(py, ty.as_type_ptr() as usize);
+    }
+    signature_key(py, obj).and_then(|(_, key)| registered_signature(py, key))
+}
+
+/// Generates the contents of a type stub (`.pyi` file) for the module.
+///
+/// The stub contains the functions, classes (including their methods)
+/// and other attributes in the module dictionary.
+/// Functions and methods that were not created by `py_fn!()` or `py_class!()`
+/// are declared as taking `*args: Any, **kwargs: Any`.
+pub fn generate_stub(py: Python, module: &PyModule) -> PyResult<String> {
+    let mut members = Vec::new();
+    for (key, value) in module.dict(py).items(py) {
+        let name: String = try!(key.extract(py));
+        if !is_special_name(&name) {
+            members.push((name, value));
+        }
+    }
+    members.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut writer = StubWriter {
+        classes: members.iter()
+            .filter(|&&(_, ref value)| value.cast_as::<PyType>(py).is_ok())
+            .map(|&(ref name, _)| name.clone())
+            .collect(),
+        annotations: type_annotations(py),
+        typing: BTreeSet::new(),
+        modules: BTreeSet::new(),
+        body: String::new()
+    };
+    for &(ref name, ref value) in &members {
+        if !writer.body.is_empty() {
+            writer.body.push('\n');
+        }
+        if let Ok(ty) = value.cast_as::<PyType>(py) {
+            try!(writer.write_class(py, name, ty));
+        } else if !writer.write_callable(py, "", name, value) {
+            let ty = writer.value_type(py, value);
+            writer.body.push_str(&format!("{}: {}\n", name, ty));
+        }
+    }
+
+    let mut stub = format!("# Type stubs for the `{}` module.\n", try!(module.name(py)));
+    if !writer.modules.is_empty() {
+        stub.push('\n');
+        for name in &writer.modules {
+            stub.push_str(&format!("import {}\n", name));
+        }
+    }
+    if !writer.typing.is_empty() {
+        let names: Vec<&str> = writer.typing.iter().cloned().collect();
+        stub.push_str(&format!("\nfrom typing import {}\n", names.join(", ")));
+    }
+    stub.push('\n');
+    stub.push_str(&writer.body);
+    Ok(stub)
+}
+
+/// Writes the type stub for the module into the directory `dir`.
+///
+/// The file is named after the module (`<name>.pyi`).
+/// Returns the path of the written file.
+pub fn write_stub<P: AsRef<Path>>(py: Python, module: &PyModule, dir: P) -> PyResult<PathBuf> {
+    let stub = try!(generate_stub(py, module));
+    let path = dir.as_ref().join(format!("{}.pyi", try!(module.name(py))));
+    let result = File::create(&path).and_then(|mut f| f.write_all(stub.as_bytes()));
+    match result {
+        Ok(()) => Ok(path),
+        Err(e) => Err(PyErr::new::<exc::IOError, _>(py, format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// Collects the type annotations of this crate's conversions and the registered annotations.
+fn type_annotations(py: Python) -> HashMap<&'static str, TypeAnnotation> {
+    let mut annotations = HashMap::new();
+    for &(paths, annotation) in TYPE_ANNOTATIONS {
+        for &path in paths {
+            annotations.insert(path, annotation);
+        }
+    }
+    annotations.extend(registry(py).borrow().annotations.iter().map(|(&k, &v)| (k, v)));
+    annotations
+}
+
+fn is_special_name(name: &str) -> bool {
+    name.len() > 4 && name.starts_with("__") && name.ends_with("__")
+}
+
+struct StubWriter {
+    /// Names of the classes in the module
+    classes: HashSet<String>,
+    /// Annotations for Rust types, keyed by path
+    annotations: HashMap<&'static str, TypeAnnotation>,
+    /// Names that need to be imported from the `typing` module
+    typing: BTreeSet<&'static str>,
+    /// Other modules whose types are referenced by the stub
+    modules: BTreeSet<&'static str>,
+    body: String
+}
+
+impl StubWriter {
+    fn write_class(&mut self, py: Python, name: &str, ty: &PyType) -> PyResult<()> {
+        let tp = ty.as_type_ptr();
+        let base = unsafe { (*tp).tp_base };
+        let base_name = if base.is_null() || base == unsafe { &mut ffi::PyBaseObject_Type as *mut _ } {
+            None
+        } else {
+            let base = unsafe { PyType::from_type_ptr(py, base) };
+            try!(self.base_name(py, &base))
+        };
+        match base_name {
+            Some(base_name) => self.body.push_str(&format!("class {}({}):", name, base_name)),
+            None => self.body.push_str(&format!("class {}:", name))
+        }
+        let header_len = self.body.len();
+        self.body.push('\n');
+
+        if let Some(sig) = registered_signature(py, tp as usize) {
+            let ret = self.return_type(&sig);
+            self.write_function("    ", "__new__", Some("cls"), &sig, ret);
+        }
+
+        let dict = unsafe { PyObject::from_borrowed_ptr(py, (*tp).tp_dict) };
+        let members = try!(dict.cast_into::<PyDict>(py)).items(py);
+        let mut members: Vec<(String, PyObject)> = try!(members.into_iter()
+            .map(|(key, value)| Ok((try!(key.extract::<String>(py)), value)))
+            .collect::<PyResult<_>>());
+        members.sort_by(|a, b| a.0.cmp(&b.0));
+        for &(ref member_name, ref value) in &members {
+            if member_name == "__new__" {
+                continue;
+            }
+            if !self.write_callable(py, "    ", member_name, value) && !is_special_name(member_name) {
+                let member_ty = self.value_type(py, value);
+                self.body.push_str(&format!("    {}: {}\n", member_name, member_ty));
+            }
+        }
+
+        if self.body.len() == header_len + 1 {
+            // no members
+            self.body.truncate(header_len);
+            self.body.push_str(" ...\n");
+        }
+        Ok(())
+    }
+
+    fn base_name(&mut self, py: Python, base: &PyType) -> PyResult<Option<String>> {
+        let name = base.name(py);
+        let name = name.rsplit('.').next().unwrap();
+        if self.classes.contains(name) {
+            return Ok(Some(name.to_owned()));
+        }
+        let module: String = try!(try!(base.as_object().getattr(py, "__module__")).extract(py));
+        match &*module {
+            "builtins" | "__builtin__" | "exceptions" => Ok(Some(name.to_owned())),
+            _ => Ok(None)
+        }
+    }
+
+    /// Writes the declaration of a function or method.
+    /// Returns false if `value` is not a function or method.
+    fn write_callable(&mut self, py: Python, indent: &str, name: &str, value: &PyObject) -> bool {
+        let (kind, key) = match signature_key(py, value) {
+            Some(k) => k,
+            None => return false
+        };
+        let sig = registered_signature(py, key);
+        if sig.is_none() && is_special_name(name) {
+            // ignore special methods implemented by the Python runtime
+            return true;
+        }
+        let first = match kind {
+            CallableKind::Function => {
+                if !indent.is_empty() {
+                    self.body.push_str(&format!("{}@staticmethod\n", indent));
+                }
+                None
+            }
+            CallableKind::InstanceMethod => Some("self"),
+            CallableKind::ClassMethod => {
+                self.body.push_str(&format!("{}@classmethod\n", indent));
+                Some("cls")
+            }
+        };
+        match sig {
+            Some(sig) => {
+                let ret = self.return_type(&sig);
+                self.write_function(indent, name, first, &sig, ret);
+            }
+            None => {
+                let any = self.any();
+                let mut params: Vec<String> = first.into_iter().map(|s| s.to_owned()).collect();
+                params.push(format!("*args: {}", any));
+                params.push(format!("**kwargs: {}", any));
+                self.body.push_str(&format!("{}def {}({}) -> {}: ...\n",
+                    indent, name, params.join(", "), any));
+            }
+        }
+        true
+    }
+
+    fn write_function(&mut self, indent: &str, name: &str, first: Option<&str>,
+                      sig: &FunctionSignature, return_type: String) {
+        let mut params: Vec<String> = first.into_iter().map(|s| s.to_owned()).collect();
+        let mut after_star = sig.params.iter().any(|p| p.kind == ParamSignatureKind::VarPositional);
+        for (i, p) in sig.params.iter().enumerate() {
+            let mut param = match p.kind {
+                ParamSignatureKind::VarPositional => format!("*{}: {}", p.name, self.any()),
+                ParamSignatureKind::VarKeyword => format!("**{}: {}", p.name, self.any()),
+                ParamSignatureKind::Regular(_) => format!("{}: {}", p.name, self.python_type(p.type_name))
+            };
+            if let Some(default) = p.default {
+                param.push_str(" = ");
+                param.push_str(&python_default(default));
+            }
+            if p.kind == ParamSignatureKind::Regular(ParamKind::KeywordOnly) && !after_star {
+                params.push("*".to_owned());
+                after_star = true;
+            }
+            params.push(param);
+            let positional_only = ParamSignatureKind::Regular(ParamKind::PositionalOnly);
+            if p.kind == positional_only && sig.params.get(i + 1).map(|p| p.kind) != Some(positional_only) {
+                params.push("/".to_owned());
+            }
+        }
+        self.body.push_str(&format!("{}def {}({}) -> {}: ...\n",
+            indent, name, params.join(", "), return_type));
+    }
+
+    fn return_type(&mut self, sig: &FunctionSignature) -> String {
+        match sig.return_type {
+            Some(return_type) => self.python_type(return_type),
+            None => self.any()
+        }
+    }
+
+    fn any(&mut self) -> String {
+        self.typing.insert("Any");
+        "Any".to_owned()
+    }
+
+    fn generic(&mut self, name: &'static str, args: &[&str]) -> String {
+        let args: Vec<String> = args.iter().map(|t| self.python_type(t)).collect();
+        self.typing.insert(name);
+        format!("{}[{}]", name, args.join(", "))
+    }
+
+    /// Gets the Python type annotation for the Rust type `name`, as written in a declaration.
+    fn python_type(&mut self, name: &str) -> String {
+        self.normalized_python_type(&normalize_type(name))
+    }
+
+    fn normalized_python_type(&mut self, name: &str) -> String {
+        if name.starts_with('&') {
+            let mut inner = name[1..].trim_start();
+            if inner.starts_with('\'') {
+                // skip the lifetime
+                inner = inner[1..].trim_start_matches(is_word_char).trim_start();
+            }
+            if inner.starts_with("mut") && !inner[3..].starts_with(is_word_char) {
+                inner = inner[3..].trim_start();
+            }
+            return self.normalized_python_type(inner);
+        }
+        if name.starts_with('(') && name.ends_with(')') {
+            let items = split_top_level(&name[1..name.len() - 1], ',');
+            if items.is_empty() {
+                return "None".to_owned();
+            }
+            return self.generic("Tuple", &items);
+        }
+        if name.starts_with('[') && name.ends_with(']') {
+            // slice `[T]` or array `[T; N]`
+            let inner = &name[1..name.len() - 1];
+            let element = split_top_level(inner, ';')[0];
+            if element == "u8" && !inner.contains(';') {
+                // `&[u8]` parameters accept `bytes` and other buffers
+                return "bytes".to_owned();
+            }
+            return self.generic("List", &[element]);
+        }
+        let (path, args) = match name.find('<') {
+            Some(pos) if name.ends_with('>') => (&name[..pos], split_top_level(&name[pos + 1..name.len() - 1], ',')),
+            _ => (name, Vec::new())
+        };
+        let path = type_path(path);
+        // lifetime arguments (as in `Cow<'a, str>`) don't affect the annotation
+        let args: Vec<&str> = args.into_iter().filter(|arg| !arg.starts_with('\'')).collect();
+        match self.annotations.get(path).cloned() {
+            Some(TypeAnnotation::Builtin(name)) => name.to_owned(),
+            Some(TypeAnnotation::Module(module, name)) => {
+                self.modules.insert(module);
+                format!("{}.{}", module, name)
+            }
+            Some(TypeAnnotation::Generic(name)) if !args.is_empty() => self.generic(name, &args),
+            Some(TypeAnnotation::Union(names)) => {
+                self.typing.insert("Union");
+                format!("Union[{}]", names.join(", "))
+            }
+            Some(TypeAnnotation::Transparent) if !args.is_empty() => self.normalized_python_type(args[0]),
+            Some(_) => self.any(),
+            None => {
+                // classes declared with `py_class!()` in the module are referred to by name
+                let last = path.rsplit("::").next().unwrap();
+                if args.is_empty() && self.classes.contains(last) {
+                    last.to_owned()
+                } else {
+                    self.any()
+                }
+            }
+        }
+    }
+
+    /// Gets the Python type annotation for the type of `value`.
+    fn value_type(&mut self, py: Python, value: &PyObject) -> String {
+        let ty = value.get_type(py);
+        let name = ty.name(py);
+        if self.classes.contains(&*name) {
+            return name.into_owned();
+        }
+        match &*name {
+            "int" | "long" => "int".to_owned(),
+            "str" | "unicode" => "str".to_owned(),
+            "NoneType" => "None".to_owned(),
+            "float" | "bool" | "bytes" | "bytearray" | "complex" | "slice"
+            | "tuple" | "list" | "dict" | "set" | "frozenset" => name.into_owned(),
+            _ => self.any()
+        }
+    }
+}
+
+/// Removes the whitespace that `stringify!()` may insert between the tokens of a type,
+/// except where it separates two words (as in `&'a str` or `&mut T`).
+fn normalize_type(name: &str) -> String {
+    fn is_punct(c: char) -> bool {
+        "<>,()[];:".contains(c)
+    }
+    let mut result = String::with_capacity(name.len());
+    let mut space = false;
+    for c in name.trim().chars() {
+        if c.is_whitespace() {
+            space = true;
+            continue;
+        }
+        if space && !is_punct(c) && !result.ends_with(is_punct) {
+            result.push(' ');
+        }
+        space = false;
+        result.push(c);
+    }
+    result
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Strips the leading `::` and the `cpython::` prefix from the path of a type.
+fn type_path(path: &str) -> &str {
+    let path = path.trim_start_matches("::");
+    for prefix in &["$crate::", "cpython::"] {
+        if path.starts_with(prefix) {
+            return &path[prefix.len()..];
+        }
+    }
+    path
+}
+
+/// Splits `s` at each occurrence of `sep` that is not nested within brackets.
+fn split_top_level(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            c if c == sep && depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    let last = s[start..].trim();
+    if !last.is_empty() {
+        parts.push(last);
+    }
+    parts
+}
+
+/// Translates a Rust default value expression into Python.
+/// Expressions that don't have an obvious Python equivalent are written as `...`.
+fn python_default(expr: &str) -> Cow<'static, str> {
+    match expr {
+        "true" => return Cow::Borrowed("True"),
+        "false" => return Cow::Borrowed("False"),
+        "None" => return Cow::Borrowed("None"),
+        _ => {}
+    }
+    let number = expr.trim_start_matches('-').replace('_', "");
+    if !number.is_empty() && number.chars().all(|c| c.is_digit(10) || c == '.')
+        && number.parse::<f64>().is_ok() {
+        return Cow::Owned(expr.replace('_', ""));
+    }
+    if expr.len() >= 2 && expr.starts_with('"') && expr.ends_with('"') && !expr.contains('\\') {
+        return Cow::Owned(expr.to_owned());
+    }
+    Cow::Borrowed("...")
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_fn_signature {
+    // `$return_type` is `{}` if the return type is unknown, or `{ type }`.
+    ($name:expr, $return_type:tt, [ $( { $pname:ident : $ptype:ty = $detail:tt } )* ]) => {
+        $crate::stubs::FunctionSignature {
+            name: $name,
+            params: vec![ $( py_param_signature!($pname : $ptype = $detail) ),* ],
+            return_type: py_return_type_signature!($return_type)
+        }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_return_type_signature {
+    ({}) => (None);
+    ({ $return_type:ty }) => (Some(stringify!($return_type)));
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_param_signature {
+    ($pname:ident : $ptype:ty = [ $kind:tt {} $rtype:tt ]) => {
+        $crate::stubs::ParamSignature {
+            name: stringify!($pname),
+            type_name: stringify!($ptype),
+            default: None,
+            kind: py_param_signature_kind!($kind)
+        }
+    };
+    ($pname:ident : $ptype:ty = [ $kind:tt {$default:expr} $rtype:tt ]) => {
+        $crate::stubs::ParamSignature {
+            name: stringify!($pname),
+            type_name: stringify!($ptype),
+            default: Some(stringify!($default)),
+            kind: py_param_signature_kind!($kind)
+        }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! py_param_signature_kind {
+    ({*}) => ($crate::stubs::ParamSignatureKind::VarPositional);
+    ({**}) => ($crate::stubs::ParamSignatureKind::VarKeyword);
+    ($kind:tt) => ($crate::stubs::ParamSignatureKind::Regular(py_argparse_param_kind!($kind)));
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashSet, BTreeSet};
+    use python::Python;
+    use super::{StubWriter, TypeAnnotation, python_default, register_type_annotation, type_annotations};
+
+    fn python_type(name: &str) -> String {
+        let gil = Python::acquire_gil();
+        let mut classes = HashSet::new();
+        classes.insert("MyClass".to_owned());
+        let mut writer = StubWriter {
+            classes: classes,
+            annotations: type_annotations(gil.python()),
+            typing: BTreeSet::new(),
+            modules: BTreeSet::new(),
+            body: String::new()
+        };
+        writer.python_type(name)
+    }
+
+    #[test]
+    fn test_python_type() {
+        assert_eq!(python_type("i32"), "int");
+        assert_eq!(python_type("f64"), "float");
+        assert_eq!(python_type("&str"), "str");
+        assert_eq!(python_type("&'a str"), "str");
+        assert_eq!(python_type("String"), "str");
+        assert_eq!(python_type("::std::string::String"), "str");
+        assert_eq!(python_type("()"), "None");
+        assert_eq!(python_type("Vec<u64>"), "List[int]");
+        assert_eq!(python_type("Vec < u64 >"), "List[int]");
+        assert_eq!(python_type("&[bool]"), "List[bool]");
+        assert_eq!(python_type("&[u8]"), "bytes");
+        assert_eq!(python_type("& mut [u8]"), "bytes");
+        assert_eq!(python_type("&'a [u8]"), "bytes");
+        assert_eq!(python_type("&mutable"), "Any");
+        assert_eq!(python_type("Option<&str>"), "Optional[str]");
+        assert_eq!(python_type("PyResult<(i32, String)>"), "Tuple[int, str]");
+        assert_eq!(python_type("std::collections::HashMap<String, Vec<f32>>"), "Dict[str, List[float]]");
+        assert_eq!(python_type("&PyTuple"), "tuple");
+        assert_eq!(python_type("&cpython::PyTuple"), "tuple");
+        assert_eq!(python_type("&$crate::PyObject"), "Any");
+        assert_eq!(python_type("SliceOrIndex"), "Union[int, slice]");
+        assert_eq!(python_type("Duration"), "datetime.timedelta");
+        assert_eq!(python_type("chrono::NaiveDate"), "datetime.date");
+        assert_eq!(python_type("Complex<f64>"), "complex");
+        assert_eq!(python_type("std::path::PathBuf"), "str");
+        assert_eq!(python_type("PyObject"), "Any");
+        assert_eq!(python_type("MyClass"), "MyClass");
+        assert_eq!(python_type("mymodule::MyClass"), "MyClass");
+        assert_eq!(python_type("OtherStruct"), "Any");
+        assert_eq!(python_type("Cow<'a, str>"), "str");
+        assert_eq!(python_type("mymodule::Path"), "Any");
+        assert_eq!(python_type("mymodule::Duration"), "Any");
+        assert_eq!(python_type("mymodule::Complex<f64>"), "Any");
+    }
+
+    #[test]
+    fn test_register_type_annotation() {
+        let gil = Python::acquire_gil();
+        register_type_annotation(gil.python(), "units::Celsius", TypeAnnotation::Builtin("float"));
+        assert_eq!(python_type("units::Celsius"), "float");
+        assert_eq!(python_type("Vec<::units::Celsius>"), "List[float]");
+        assert_eq!(python_type("Celsius"), "Any");
+    }
+
+    #[test]
+    fn test_python_default() {
+        assert_eq!(python_default("true"), "True");
+        assert_eq!(python_default("None"), "None");
+        assert_eq!(python_default("-1_000"), "-1000");
+        assert_eq!(python_default("0.5"), "0.5");
+        assert_eq!(python_default("\"utf-8\""), "\"utf-8\"");
+        assert_eq!(python_default("Vec::new()"), "...");
+        assert_eq!(python_default("Some(5)"), "...");
+    }
+}
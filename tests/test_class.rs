@@ -451,6 +451,51 @@ fn sequence() {
 }
 
 
+py_class!(class SliceableSequence |py| {
+    data items: Vec<i32>;
+
+    def __len__(&self) -> PyResult<usize> {
+        Ok(self.items(py).len())
+    }
+
+    def __getitem__(&self, key: SliceOrIndex) -> PyResult<PyObject> {
+        let data = self.items(py);
+        match key {
+            SliceOrIndex::Index(i) => {
+                let i = if i < 0 { i + data.len() as isize } else { i };
+                match data.get(i as usize) {
+                    Some(v) if i >= 0 => Ok(v.to_py_object(py).into_object()),
+                    _ => Err(PyErr::new::<exc::IndexError, NoArgs>(py, NoArgs))
+                }
+            }
+            SliceOrIndex::Slice(slice) => {
+                let indices = try!(slice.indices(py, data.len()));
+                let result: Vec<i32> = (0..indices.slicelength as isize)
+                    .map(|i| data[(indices.start + i * indices.step) as usize])
+                    .collect();
+                Ok(result.to_py_object(py).into_object())
+            }
+        }
+    }
+});
+
+#[test]
+fn sequence_slicing() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c = SliceableSequence::create_instance(py, vec![10, 20, 30, 40, 50]).unwrap();
+    py_assert!(py, c, "c[1] == 20");
+    py_assert!(py, c, "c[-1] == 50");
+    py_assert!(py, c, "c[1:4] == [20, 30, 40]");
+    py_assert!(py, c, "c[::-2] == [50, 30, 10]");
+    py_assert!(py, c, "c[10:] == []");
+    py_assert!(py, c, "list(c) == [10, 20, 30, 40, 50]");
+    py_expect_exception!(py, c, "c[5]", IndexError);
+    py_expect_exception!(py, c, "c[1.5]", TypeError);
+    py_expect_exception!(py, c, "c[::0]", ValueError);
+}
+
 py_class!(class Callable |py| {
     def __call__(&self, arg: i32) -> PyResult<i32> {
         Ok(arg * 6)
@@ -862,3 +907,53 @@ fn context_manager() {
     assert!(c.exit_called(py).get());
 }
 
+
+py_class!(class StubClass |py| {
+    data value: i32;
+    static LIMIT = 10;
+
+    def __new__(_cls, value: i32, label: Option<String> = None) -> PyResult<StubClass> {
+        StubClass::create_instance(py, value)
+    }
+
+    def scaled(&self, factor: f64, *, round: bool = false) -> PyResult<Vec<f64>> {
+        Ok(vec![*self.value(py) as f64 * factor])
+    }
+
+    @classmethod
+    def create(cls, values: Vec<i32>) -> PyResult<Option<StubClass>> {
+        Ok(None)
+    }
+
+    @staticmethod
+    def describe(obj: StubClass) -> PyResult<String> {
+        Ok(String::new())
+    }
+});
+
+#[test]
+#[cfg(feature="stubs")]
+fn stub_generation() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let m = PyModule::new(py, "stubtest").unwrap();
+    m.add_class::<StubClass>(py).unwrap();
+    m.add_class::<EmptyClass>(py).unwrap();
+
+    assert_eq!(cpython::stubs::generate_stub(py, &m).unwrap(), "\
+# Type stubs for the `stubtest` module.
+
+from typing import List, Optional
+
+class EmptyClass: ...
+
+class StubClass:
+    def __new__(cls, value: int, label: Optional[str] = None) -> StubClass: ...
+    LIMIT: int
+    @classmethod
+    def create(cls, values: List[int]) -> Optional[StubClass]: ...
+    @staticmethod
+    def describe(obj: StubClass) -> str: ...
+    def scaled(self, factor: float, *, round: bool = False) -> List[float]: ...
+");
+}
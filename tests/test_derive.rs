@@ -0,0 +1,197 @@
+#[macro_use] extern crate cpython;
+#[macro_use] extern crate cpython_derive;
+
+use cpython::{Python, PyDict, PyErr, PyObject, PyResult, PythonObject, ToPyObject, ObjectProtocol};
+
+fn run(py: Python, code: &str) -> PyObject {
+    py.eval(code, None, None).unwrap()
+}
+
+fn error_message(py: Python, mut err: PyErr) -> String {
+    err.instance(py).str(py).unwrap().to_string_lossy(py).into_owned()
+}
+
+#[derive(Debug, PartialEq, ToPyObject, FromPyObject)]
+struct Point {
+    x: i32,
+    #[cpython(rename = "y_coord")]
+    y: i32,
+    #[cpython(default)]
+    label: Option<String>,
+    #[cpython(default = "default_scale")]
+    scale: f64,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+#[test]
+fn dict_struct() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let p: Point = run(py, "{'x': 1, 'y_coord': 2}").extract(py).unwrap();
+    assert_eq!(p, Point { x: 1, y: 2, label: None, scale: 1.0 });
+
+    let p = Point { x: 3, y: 4, label: Some("a".to_owned()), scale: 0.5 };
+    let obj = p.to_py_object(py);
+    let dict = obj.cast_as::<PyDict>(py).unwrap();
+    assert_eq!(dict.len(py), 4);
+    assert_eq!(dict.get_item(py, "y_coord").unwrap().extract::<i32>(py).unwrap(), 4);
+    assert_eq!(obj.extract::<Point>(py).unwrap(), p);
+}
+
+#[test]
+fn dict_struct_errors() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let err = run(py, "{'x': 1}").extract::<Point>(py).unwrap_err();
+    assert!(err.matches(py, py.get_type::<cpython::exc::KeyError>()));
+    assert!(error_message(py, err).contains("missing key 'y_coord' for Point"));
+
+    let err = run(py, "{'x': 'a', 'y_coord': 2}").extract::<Point>(py).unwrap_err();
+    assert!(err.matches(py, py.get_type::<cpython::exc::TypeError>()));
+    assert!(error_message(py, err).starts_with("failed to extract Point.x: "));
+}
+
+#[derive(Debug, PartialEq, FromPyObject)]
+#[cpython(attributes)]
+struct Config {
+    name: String,
+    #[cpython(default)]
+    verbose: bool,
+}
+
+#[test]
+fn attributes_struct() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let c: Config = run(py, "type('C', (object,), {'name': 'test'})()").extract(py).unwrap();
+    assert_eq!(c, Config { name: "test".to_owned(), verbose: false });
+
+    let err = run(py, "object()").extract::<Config>(py).unwrap_err();
+    assert!(err.matches(py, py.get_type::<cpython::exc::AttributeError>()));
+}
+
+#[test]
+fn attributes_struct_to_namespace() {
+    #[derive(Debug, PartialEq, ToPyObject, FromPyObject)]
+    #[cpython(attributes)]
+    struct Version {
+        major: u32,
+        minor: u32,
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = Version { major: 3, minor: 6 }.into_py_object(py);
+    assert_eq!(obj.getattr(py, "minor").unwrap().extract::<u32>(py).unwrap(), 6);
+    assert_eq!(obj.extract::<Version>(py).unwrap(), Version { major: 3, minor: 6 });
+    // the second conversion uses the cached namespace type
+    let obj = Version { major: 2, minor: 7 }.to_py_object(py);
+    assert_eq!(obj.getattr(py, "major").unwrap().extract::<u32>(py).unwrap(), 2);
+}
+
+#[derive(Debug, PartialEq, ToPyObject, FromPyObject)]
+struct Pair(i32, String);
+
+#[derive(Debug, PartialEq, ToPyObject, FromPyObject)]
+#[cpython(tuple)]
+struct Range {
+    start: i32,
+    end: i32,
+}
+
+#[derive(Debug, PartialEq, ToPyObject, FromPyObject)]
+struct Meters(f64);
+
+#[derive(Debug, PartialEq, ToPyObject, FromPyObject)]
+struct Nothing;
+
+#[test]
+fn tuple_structs() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    assert_eq!(run(py, "(1, 'a')").extract::<Pair>(py).unwrap(), Pair(1, "a".to_owned()));
+    assert_eq!(run(py, "[1, 'a']").extract::<Pair>(py).unwrap(), Pair(1, "a".to_owned()));
+    let obj = Pair(2, "b".to_owned()).to_py_object(py);
+    assert_eq!(obj.extract::<(i32, String)>(py).unwrap(), (2, "b".to_owned()));
+
+    let err = run(py, "(1, 2, 3)").extract::<Range>(py).unwrap_err();
+    assert!(err.matches(py, py.get_type::<cpython::exc::ValueError>()));
+    assert_eq!(error_message(py, err), "expected a tuple of length 2 for Range, got length 3");
+    assert_eq!(run(py, "(1, 5)").extract::<Range>(py).unwrap(), Range { start: 1, end: 5 });
+
+    assert_eq!(run(py, "2.5").extract::<Meters>(py).unwrap(), Meters(2.5));
+    assert_eq!(Meters(1.5).to_py_object(py).extract::<f64>(py).unwrap(), 1.5);
+
+    assert_eq!(Nothing.to_py_object(py), py.None());
+    assert_eq!(py.None().extract::<Nothing>(py).unwrap(), Nothing);
+}
+
+#[derive(Debug, PartialEq, ToPyObject, FromPyObject)]
+enum Shape {
+    Circle { radius: f64 },
+    #[cpython(tuple)]
+    Rect { width: f64, height: f64 },
+    #[cpython(rename = "empty")]
+    Empty,
+    Named(String),
+}
+
+#[test]
+fn enums() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    assert_eq!(run(py, "{'radius': 2.0}").extract::<Shape>(py).unwrap(), Shape::Circle { radius: 2.0 });
+    assert_eq!(run(py, "(1.0, 2.0)").extract::<Shape>(py).unwrap(), Shape::Rect { width: 1.0, height: 2.0 });
+    assert_eq!(run(py, "'empty'").extract::<Shape>(py).unwrap(), Shape::Empty);
+    assert_eq!(run(py, "'x'").extract::<Shape>(py).unwrap(), Shape::Named("x".to_owned()));
+
+    for shape in &[Shape::Circle { radius: 1.0 }, Shape::Rect { width: 1.0, height: 2.0 },
+                   Shape::Named("x".to_owned()), Shape::Empty] {
+        let obj = shape.to_py_object(py);
+        assert_eq!(obj.extract::<Shape>(py).unwrap(), *shape);
+    }
+    assert_eq!(Shape::Empty.into_py_object(py).extract::<String>(py).unwrap(), "empty");
+
+    let err = run(py, "3").extract::<Shape>(py).unwrap_err();
+    assert!(err.matches(py, py.get_type::<cpython::exc::TypeError>()));
+    let message = error_message(py, err);
+    assert!(message.starts_with("failed to extract Shape from any variant; Shape::Circle: "), "{}", message);
+    assert!(message.contains("; Shape::Empty: expected 'empty'"), "{}", message);
+}
+
+#[derive(Debug, PartialEq, ToPyObject, FromPyObject)]
+struct Wrapper<T> {
+    value: T,
+}
+
+#[test]
+fn generic_struct() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let obj = Wrapper { value: vec![1, 2] }.into_py_object(py);
+    assert_eq!(obj.extract::<Wrapper<Vec<i32>>>(py).unwrap(), Wrapper { value: vec![1, 2] });
+}
+
+#[test]
+fn derived_types_in_functions() {
+    fn norm(_py: Python, p: Point) -> PyResult<i32> {
+        Ok(p.x.abs() + p.y.abs())
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let f = py_fn!(py, norm(p: Point));
+    let d = PyDict::new(py);
+    d.set_item(py, "x", -1).unwrap();
+    d.set_item(py, "y_coord", 2).unwrap();
+    assert_eq!(f.call(py, (d.into_object(),), None).unwrap().extract::<i32>(py).unwrap(), 3);
+}
@@ -1,6 +1,7 @@
 #[macro_use] extern crate cpython;
 
-use cpython::{PyResult, Python, NoArgs, ObjectProtocol, PyDict};
+use cpython::{PyResult, Python, NoArgs, ObjectProtocol, PyDict, PyCFunction};
+use std::collections::{HashMap, BTreeMap};
 use std::sync::atomic;
 use std::sync::atomic::Ordering::Relaxed;
 
@@ -65,6 +66,43 @@ fn inline_two_args() {
     assert_eq!(obj.call(py, (6, 7), None).unwrap().extract::<i32>(py).unwrap(), 42);
 }
 
+#[test]
+fn map_arg() {
+    fn f(_py: Python, weights: HashMap<String, i32>, order: BTreeMap<i32, String>) -> PyResult<String> {
+        let total: i32 = weights.values().sum();
+        let names: Vec<String> = order.into_iter().map(|(_, v)| v).collect();
+        Ok(format!("{} {}", total, names.join(",")))
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py_fn!(py, f(weights: HashMap<String, i32>, order: BTreeMap<i32, String> = BTreeMap::new()));
+
+    let weights = py.eval("{'a': 1, 'b': 2}", None, None).unwrap();
+    let order = py.eval("{2: 'y', 1: 'x'}", None, None).unwrap();
+    assert_eq!(obj.call(py, (&weights,), None).unwrap().extract::<String>(py).unwrap(), "3 ");
+    assert_eq!(obj.call(py, (&weights, &order), None).unwrap().extract::<String>(py).unwrap(), "3 x,y");
+    assert!(obj.call(py, (&order,), None).is_err());
+    assert!(obj.call(py, (1,), None).is_err());
+}
+
+#[test]
+fn byte_slice_arg() {
+    fn f(_py: Python, data: &[u8]) -> PyResult<usize> {
+        Ok(data.iter().filter(|&&b| b == b'a').count())
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py_fn!(py, f(data: &[u8]));
+
+    let bytes = py.eval("b'banana'", None, None).unwrap();
+    assert_eq!(obj.call(py, (&bytes,), None).unwrap().extract::<usize>(py).unwrap(), 3);
+    let bytearray = py.eval("bytearray(b'abracadabra')", None, None).unwrap();
+    assert_eq!(obj.call(py, (&bytearray,), None).unwrap().extract::<usize>(py).unwrap(), 5);
+    assert!(obj.call(py, (1,), None).is_err());
+}
+
 /* TODO: reimplement flexible sig support
 #[test]
 fn flexible_sig() {
@@ -89,3 +127,114 @@ fn flexible_sig() {
 }
 */
 
+
+#[test]
+fn keyword_only_and_positional_only() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py_fn!(py, f(a: i32, /, b: i32 = 0, *, c: i32 = 0) -> PyResult<i32> {
+        drop(py); // avoid unused variable warning
+        Ok(a * 100 + b * 10 + c)
+    });
+
+    assert_eq!(obj.call(py, (1, 2), None).unwrap().extract::<i32>(py).unwrap(), 120);
+    assert!(obj.call(py, (1, 2, 3), None).is_err());
+
+    let dict = PyDict::new(py);
+    dict.set_item(py, "c", 3).unwrap();
+    assert_eq!(obj.call(py, (1,), Some(&dict)).unwrap().extract::<i32>(py).unwrap(), 103);
+    dict.set_item(py, "a", 1).unwrap();
+    assert!(obj.call(py, (1,), Some(&dict)).is_err());
+}
+
+#[test]
+fn call_from_python_with_keywords() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py_fn!(py, f(a: i32, b: i32 = 5) -> PyResult<i32> {
+        drop(py); // avoid unused variable warning
+        Ok(a * 10 + b)
+    });
+    let dict = PyDict::new(py);
+    dict.set_item(py, "f", obj).unwrap();
+    let result: (i32, i32, i32) = py.eval("(f(1), f(1, b=3), f(b=2, a=1))", None, Some(&dict))
+        .unwrap().extract(py).unwrap();
+    assert_eq!(result, (15, 13, 12));
+    assert!(py.eval("f(1, c=3)", None, Some(&dict)).is_err());
+    assert!(py.eval("f(1, 2, 3)", None, Some(&dict)).is_err());
+}
+
+#[test]
+fn call_varargs_from_python_with_keywords() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py_fn!(py, f(*args, **kwargs) -> PyResult<usize> {
+        Ok(args.len(py) + 100 * kwargs.map_or(0, |kwargs| kwargs.len(py)))
+    });
+    let dict = PyDict::new(py);
+    dict.set_item(py, "f", obj).unwrap();
+    assert_eq!(py.eval("f()", None, Some(&dict)).unwrap().extract::<usize>(py).unwrap(), 0);
+    assert_eq!(py.eval("f(1, 2, x=3)", None, Some(&dict)).unwrap().extract::<usize>(py).unwrap(), 102);
+}
+
+#[test]
+fn closure() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let offset = 100;
+    let f = PyCFunction::from_closure(py, "add_offset", move |py, args, kwargs| {
+        py_argparse!(py, Some("add_offset"), args, kwargs, (x: i32, y: i32 = 0) {
+            Ok(x + y + offset)
+        })
+    });
+    let dict = PyDict::new(py);
+    dict.set_item(py, "f", f).unwrap();
+    assert_eq!(py.eval("f(1)", None, Some(&dict)).unwrap().extract::<i32>(py).unwrap(), 101);
+    assert_eq!(py.eval("f(1, y=2)", None, Some(&dict)).unwrap().extract::<i32>(py).unwrap(), 103);
+    assert!(py.eval("f()", None, Some(&dict)).is_err());
+}
+
+fn stub_function(_py: Python, data: &str, count: usize, flag: bool) -> PyResult<Option<Vec<String>>> {
+    Ok(if flag { Some(vec![data.to_owned(); count]) } else { None })
+}
+
+#[test]
+fn declared_return_type() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = py_fn!(py, stub_function(data: &str, /, count: usize = 1, *, flag: bool = true)
+                         -> PyResult<Option<Vec<String>>>);
+    let dict = PyDict::new(py);
+    dict.set_item(py, "f", obj).unwrap();
+    assert_eq!(py.eval("f('a', 2)", None, Some(&dict)).unwrap().extract::<Vec<String>>(py).unwrap(), vec!["a", "a"]);
+}
+
+#[test]
+#[cfg(feature="stubs")]
+fn stub_generation() {
+    use cpython::{PyModule, PyObject};
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let m = PyModule::new(py, "stubtest").unwrap();
+    m.add(py, "stub_function", py_fn!(py, stub_function(data: &str, /, count: usize = 1, *, flag: bool = true)
+                                          -> PyResult<Option<Vec<String>>>)).unwrap();
+    m.add(py, "untyped", py_fn!(py, stub_function(data: &str, count: usize = 1, flag: bool = true))).unwrap();
+    m.add(py, "anything", py_fn!(py, anything(*args, **kwargs) -> PyResult<PyObject> {
+        Ok(py.None())
+    })).unwrap();
+    m.add(py, "VERSION", "1.0").unwrap();
+
+    assert_eq!(cpython::stubs::generate_stub(py, &m).unwrap(), "\
+# Type stubs for the `stubtest` module.
+
+from typing import Any, List, Optional
+
+VERSION: str
+
+def anything(*args: Any, **kwargs: Any) -> Any: ...
+
+def stub_function(data: str, /, count: int = 1, *, flag: bool = True) -> Optional[List[str]]: ...
+
+def untyped(data: str, count: int = 1, flag: bool = True) -> Any: ...
+");
+}